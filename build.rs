@@ -2,54 +2,82 @@ use std::env;
 use std::path::PathBuf;
 use std::process::Command;
 
+// The `cuda` feature no longer links against the CUDA toolkit at all: the
+// device kernel is compiled to PTX here (when `nvcc` is available) and
+// loaded through the CUDA *driver* API at runtime (see src/cuda_gpu.rs). This
+// means the crate builds and ships identically on machines with or without
+// CUDA installed, and the resulting binary uses the GPU only if it finds a
+// compatible driver at startup.
 fn main() {
-    // Check if CUDA is available
-    if let Ok(cuda_path) = env::var("CUDA_PATH") {
-        println!("cargo:rustc-env=CUDA_PATH={}", cuda_path);
-        build_cuda();
-    } else if let Some(cuda_path) = find_cuda() {
-        println!("cargo:rustc-env=CUDA_PATH={}", cuda_path);
-        build_cuda();
+    println!("cargo:rerun-if-changed=src/vanity_id.cu");
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // The cuda_gpu module now only *dlopen*s the driver at runtime, so it can
+    // always be compiled in, regardless of whether this host has a CUDA
+    // toolkit installed.
+    println!("cargo:rustc-cfg=feature=\"cuda\"");
+
+    // Same story for OpenCL: opencl_gpu dlopens the ICD loader and compiles
+    // its kernel from source at runtime, so there's no toolkit dependency to
+    // gate this on either.
+    println!("cargo:rustc-cfg=feature=\"opencl\"");
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR not set");
+    let ptx_path = PathBuf::from(&out_dir).join("vanity_id.ptx");
+
+    if let Some(nvcc) = find_nvcc() {
+        if compile_to_ptx(&nvcc, &ptx_path) {
+            println!("cargo:rustc-env=VANITY_PTX_PATH={}", ptx_path.display());
+            return;
+        }
+        println!("cargo:warning=nvcc was found but PTX compilation failed; falling back to a prebuilt PTX if present.");
     } else {
-        println!("cargo:warning=CUDA not found. CUDA GPU acceleration will not be available.");
-        println!("cargo:rustc-cfg=no_cuda");
+        println!("cargo:warning=nvcc not found; CUDA kernel will not be recompiled. Falling back to a prebuilt PTX if present.");
+    }
+
+    // No toolkit available at build time: fall back to a PTX checked into
+    // the repo, if the maintainers have shipped one for this kernel version.
+    let prebuilt = PathBuf::from("src/vanity_id.ptx");
+    if prebuilt.exists() {
+        println!("cargo:rustc-env=VANITY_PTX_PATH={}", prebuilt.display());
         return;
     }
+
+    println!(
+        "cargo:warning=No PTX available (no nvcc and no src/vanity_id.ptx). CUDA GPU acceleration will be unavailable at runtime; the crate still builds and runs on CPU/Metal."
+    );
+
+    // cuda_gpu.rs embeds the PTX via `include_str!(env!("VANITY_PTX_PATH"))`,
+    // which needs the env var set and the file present at compile time even
+    // when there's nothing to embed; an empty file means "no GPU kernel
+    // available" and is checked for at runtime before any driver call.
+    let empty_ptx = PathBuf::from(&out_dir).join("vanity_id.ptx");
+    std::fs::write(&empty_ptx, "").expect("failed to write placeholder PTX file");
+    println!("cargo:rustc-env=VANITY_PTX_PATH={}", empty_ptx.display());
 }
 
-fn find_cuda() -> Option<String> {
-    // Common CUDA installation paths
-    let cuda_paths = [
-        "/usr/local/cuda",
-        "/opt/cuda",
-        "/usr/cuda",
-        "C:\\Program Files\\NVIDIA GPU Computing Toolkit\\CUDA\\v12.0",
-        "C:\\Program Files\\NVIDIA GPU Computing Toolkit\\CUDA\\v11.8",
-        "C:\\Program Files\\NVIDIA GPU Computing Toolkit\\CUDA\\v11.7",
-        "C:\\Program Files\\NVIDIA GPU Computing Toolkit\\CUDA\\v11.6",
-    ];
-
-    for path in &cuda_paths {
-        let cuda_path = PathBuf::from(path);
-        if cuda_path.exists() {
-            return Some(path.to_string());
+fn find_nvcc() -> Option<PathBuf> {
+    if let Ok(cuda_path) = env::var("CUDA_PATH") {
+        let nvcc = PathBuf::from(cuda_path).join("bin").join(nvcc_name());
+        if nvcc.exists() {
+            return Some(nvcc);
+        }
+    }
+
+    for path in common_cuda_paths() {
+        let nvcc = PathBuf::from(path).join("bin").join(nvcc_name());
+        if nvcc.exists() {
+            return Some(nvcc);
         }
     }
 
-    // Try to find nvcc in PATH
-    if Command::new("nvcc").arg("--version").output().is_ok() {
-        // nvcc is available, try to get CUDA path
-        let which_cmd = if cfg!(target_os = "windows") {
-            "where"
-        } else {
-            "which"
-        };
-        if let Ok(output) = Command::new(which_cmd).arg("nvcc").output() {
-            if let Ok(nvcc_path) = String::from_utf8(output.stdout) {
-                let nvcc_path = nvcc_path.trim();
-                if let Some(cuda_path) = PathBuf::from(nvcc_path).parent().and_then(|p| p.parent())
-                {
-                    return Some(cuda_path.to_string_lossy().to_string());
+    let which_cmd = if cfg!(target_os = "windows") { "where" } else { "which" };
+    if let Ok(output) = Command::new(which_cmd).arg("nvcc").output() {
+        if output.status.success() {
+            if let Ok(path) = String::from_utf8(output.stdout) {
+                let path = path.lines().next().unwrap_or("").trim();
+                if !path.is_empty() {
+                    return Some(PathBuf::from(path));
                 }
             }
         }
@@ -58,79 +86,38 @@ fn find_cuda() -> Option<String> {
     None
 }
 
-fn build_cuda() {
-    let cuda_path = env::var("CUDA_PATH").expect("CUDA_PATH should be set");
-    let cuda_path = PathBuf::from(cuda_path);
-
-    // Set up include and library paths
-    let cuda_include = cuda_path.join("include");
-    let cuda_lib = if cfg!(target_os = "windows") {
-        cuda_path.join("lib").join("x64")
-    } else {
-        cuda_path.join("lib64")
-    };
-
-    println!("cargo:rustc-link-search=native={}", cuda_lib.display());
-    println!("cargo:rustc-link-lib=cudart");
-    println!("cargo:rustc-link-lib=cuda");
-
-    // Compile CUDA source
-    let nvcc = if cfg!(target_os = "windows") {
-        cuda_path.join("bin").join("nvcc.exe")
+fn nvcc_name() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "nvcc.exe"
     } else {
-        cuda_path.join("bin").join("nvcc")
-    };
-
-    if !nvcc.exists() {
-        panic!("nvcc not found at {}", nvcc.display());
+        "nvcc"
     }
+}
 
-    let out_dir = env::var("OUT_DIR").unwrap();
-    let cuda_source = "src/vanity_id.cu";
-    let cuda_object = PathBuf::from(&out_dir).join("vanity_id.o");
+fn common_cuda_paths() -> &'static [&'static str] {
+    &[
+        "/usr/local/cuda",
+        "/opt/cuda",
+        "/usr/cuda",
+        "C:\\Program Files\\NVIDIA GPU Computing Toolkit\\CUDA\\v12.0",
+        "C:\\Program Files\\NVIDIA GPU Computing Toolkit\\CUDA\\v11.8",
+    ]
+}
 
-    // Compile CUDA code to object file
-    let mut nvcc_cmd = Command::new(&nvcc);
-    nvcc_cmd
-        .arg("-c")
-        .arg(cuda_source)
+// Compiles the kernel to architecture-portable PTX. Unlike the old cubin
+// build, this intentionally omits `-gencode`/`-arch` pinning: the driver JITs
+// the PTX for whatever device it finds at runtime, so one build artifact
+// keeps working across GPU generations.
+fn compile_to_ptx(nvcc: &PathBuf, ptx_path: &PathBuf) -> bool {
+    let status = Command::new(nvcc)
+        .arg("--ptx")
+        .arg("src/vanity_id.cu")
         .arg("-o")
-        .arg(&cuda_object)
-        .arg("-I")
-        .arg(&cuda_include)
-        .arg("--compiler-options")
-        .arg("-fPIC"); // Position independent code for shared libraries
-
-    // Add architecture flags for better compatibility
-    nvcc_cmd.arg("-gencode").arg("arch=compute_50,code=sm_50"); // Maxwell
-    nvcc_cmd.arg("-gencode").arg("arch=compute_60,code=sm_60"); // Pascal
-    nvcc_cmd.arg("-gencode").arg("arch=compute_70,code=sm_70"); // Volta
-    nvcc_cmd.arg("-gencode").arg("arch=compute_75,code=sm_75"); // Turing
-    nvcc_cmd.arg("-gencode").arg("arch=compute_80,code=sm_80"); // Ampere
-    nvcc_cmd.arg("-gencode").arg("arch=compute_86,code=sm_86"); // Ampere
-    nvcc_cmd.arg("-gencode").arg("arch=compute_89,code=sm_89"); // Ada Lovelace
-    nvcc_cmd.arg("-gencode").arg("arch=compute_90,code=sm_90"); // Hopper
-
-    println!("Running: {:?}", nvcc_cmd);
-
-    let output = nvcc_cmd.output().expect("Failed to execute nvcc");
-
-    if !output.status.success() {
-        panic!(
-            "nvcc compilation failed:\nstdout: {}\nstderr: {}",
-            String::from_utf8_lossy(&output.stdout),
-            String::from_utf8_lossy(&output.stderr)
-        );
-    }
-
-    // Link the object file
-    println!("cargo:rustc-link-search=native={}", out_dir);
-    println!("cargo:rustc-link-arg={}", cuda_object.display());
+        .arg(ptx_path)
+        .status();
 
-    // Tell cargo to rerun if CUDA source changes
-    println!("cargo:rerun-if-changed=src/vanity_id.cu");
-    println!("cargo:rerun-if-changed=build.rs");
-
-    // Enable CUDA feature
-    println!("cargo:rustc-cfg=feature=\"cuda\"");
+    match status {
+        Ok(status) => status.success(),
+        Err(_) => false,
+    }
 }
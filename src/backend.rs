@@ -0,0 +1,97 @@
+//! Common entry point over every vanity-id search implementation (GPU or
+//! CPU), so callers don't need to know at compile time which backends are
+//! available on the host.
+
+use crate::match_criteria::MatchCriteria;
+use rayon::prelude::*;
+
+/// Implemented by every search backend (Metal, CUDA, OpenCL, CPU, and any
+/// future backends) so callers have one API regardless of which GPU
+/// (if any) is present on the host.
+pub trait VanityBackend {
+    fn search(
+        &self,
+        criteria: &MatchCriteria,
+        start_counter: u64,
+        batch_size: u64,
+    ) -> Result<Option<(u64, [u8; 32])>, Box<dyn std::error::Error>>;
+
+    fn device_name(&self) -> String;
+
+    /// A reasonable default batch size for this backend's dispatch overhead;
+    /// callers may still override it via `--gpu-batch-size`.
+    fn preferred_batch_size(&self) -> u64;
+}
+
+/// Scalar/rayon-backed CPU implementation of `VanityBackend`, used as the
+/// universal fallback when no GPU backend is available.
+pub struct CpuVanityGenerator {
+    num_threads: usize,
+}
+
+impl CpuVanityGenerator {
+    pub fn new() -> Self {
+        CpuVanityGenerator {
+            num_threads: num_cpus::get(),
+        }
+    }
+}
+
+impl Default for CpuVanityGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VanityBackend for CpuVanityGenerator {
+    fn search(
+        &self,
+        criteria: &MatchCriteria,
+        start_counter: u64,
+        batch_size: u64,
+    ) -> Result<Option<(u64, [u8; 32])>, Box<dyn std::error::Error>> {
+        let found = (0..batch_size).into_par_iter().find_map_any(|offset| {
+            let counter = start_counter + offset;
+            crate::try_generate_match_optimized(criteria, counter)
+                .map(|(_, key_data)| (counter, key_data))
+        });
+        Ok(found)
+    }
+
+    fn device_name(&self) -> String {
+        format!("CPU ({} threads)", self.num_threads)
+    }
+
+    fn preferred_batch_size(&self) -> u64 {
+        500_000
+    }
+}
+
+/// Factory that probes backends in priority order and returns whichever one
+/// is actually usable on this host.
+pub struct VanityEngine;
+
+impl VanityEngine {
+    /// Probes CUDA, then Metal, then falls back to the CPU backend, which is
+    /// always available.
+    pub fn auto() -> Box<dyn VanityBackend> {
+        #[cfg(feature = "cuda")]
+        {
+            if let Ok(gpu) = crate::cuda_gpu::CudaVanityGenerator::new() {
+                println!("Auto-selected backend: CUDA ({})", gpu.get_device_name());
+                return Box::new(gpu);
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            if let Ok(gpu) = crate::gpu::GpuVanityGenerator::new() {
+                println!("Auto-selected backend: Metal ({})", gpu.get_device_name());
+                return Box::new(gpu);
+            }
+        }
+
+        println!("Auto-selected backend: CPU");
+        Box::new(CpuVanityGenerator::new())
+    }
+}
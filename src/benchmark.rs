@@ -0,0 +1,246 @@
+//! `--benchmark` mode: times each available backend (CPU, GPU, hybrid) for a
+//! fixed duration against criteria chosen so they never match, then reports
+//! sustained keys/sec for each. Lets users compare backends and tune
+//! `--gpu-batch-size` without waiting for a real search to find anything.
+//! Modeled on ccminer's `--benchmark`: iterate every algorithm, keep all
+//! results in memory, print a summary table at the end, optionally export it
+//! as JSON.
+
+use crate::backend::VanityBackend;
+use crate::match_criteria::MatchCriteria;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct BenchmarkResult {
+    pub backend: String,
+    pub device: String,
+    pub batch_size: u64,
+    pub attempts: u64,
+    pub keys_per_sec: f64,
+}
+
+impl BenchmarkResult {
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"backend\":{},\"device\":{},\"batch_size\":{},\"attempts\":{},\"keys_per_sec\":{:.2}}}",
+            json_string(&self.backend),
+            json_string(&self.device),
+            self.batch_size,
+            self.attempts,
+            self.keys_per_sec
+        )
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Runs `backend` against the unmatchable prefix for `duration`, dispatching
+/// `batch_size` counters at a time, and reports sustained throughput.
+pub fn benchmark_backend(
+    name: &str,
+    backend: &dyn VanityBackend,
+    batch_size: u64,
+    duration: Duration,
+) -> BenchmarkResult {
+    let device = backend.device_name();
+    println!("Benchmarking {} ({})...", name, device);
+
+    let criteria = MatchCriteria::unmatchable();
+    let start = Instant::now();
+    let mut attempts = 0u64;
+    let mut batch_id = 0u64;
+
+    while start.elapsed() < duration {
+        let batch_start = batch_id * batch_size;
+        match backend.search(&criteria, batch_start, batch_size) {
+            Ok(_) => {
+                attempts += batch_size;
+                batch_id += 1;
+            }
+            Err(e) => {
+                eprintln!("{} error during benchmark: {}", name, e);
+                break;
+            }
+        }
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    let keys_per_sec = if elapsed > 0.0 {
+        attempts as f64 / elapsed
+    } else {
+        0.0
+    };
+
+    BenchmarkResult {
+        backend: name.to_string(),
+        device,
+        batch_size,
+        attempts,
+        keys_per_sec,
+    }
+}
+
+/// Runs `num_cpu_threads` CPU threads and `gpu` simultaneously for
+/// `duration` and reports their combined throughput as a single "Hybrid"
+/// row. Unlike the real hybrid search, the two sides share no counter
+/// dispenser: the GPU and every CPU thread each count up from 0.
+pub fn benchmark_hybrid(
+    gpu: &dyn VanityBackend,
+    gpu_batch_size: u64,
+    num_cpu_threads: usize,
+    duration: Duration,
+) -> BenchmarkResult {
+    println!(
+        "Benchmarking Hybrid ({} + {} CPU threads)...",
+        gpu.device_name(),
+        num_cpu_threads
+    );
+
+    let criteria = MatchCriteria::unmatchable();
+    let start = Instant::now();
+    let gpu_attempts = AtomicU64::new(0);
+    let cpu_attempts = AtomicU64::new(0);
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let mut batch_id = 0u64;
+            while start.elapsed() < duration {
+                let batch_start = batch_id * gpu_batch_size;
+                match gpu.search(&criteria, batch_start, gpu_batch_size) {
+                    Ok(_) => {
+                        gpu_attempts.fetch_add(gpu_batch_size, Ordering::Relaxed);
+                        batch_id += 1;
+                    }
+                    Err(e) => {
+                        eprintln!("Hybrid GPU error during benchmark: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        for _ in 0..num_cpu_threads {
+            scope.spawn(|| {
+                let mut counter = 0u64;
+                let mut local_attempts = 0u64;
+                while start.elapsed() < duration {
+                    crate::try_generate_match_optimized(&criteria, counter);
+                    counter += 1;
+                    local_attempts += 1;
+                }
+                cpu_attempts.fetch_add(local_attempts, Ordering::Relaxed);
+            });
+        }
+    });
+
+    let elapsed = start.elapsed().as_secs_f64();
+    let total_attempts =
+        gpu_attempts.load(Ordering::Relaxed) + cpu_attempts.load(Ordering::Relaxed);
+    let keys_per_sec = if elapsed > 0.0 {
+        total_attempts as f64 / elapsed
+    } else {
+        0.0
+    };
+
+    BenchmarkResult {
+        backend: "Hybrid".to_string(),
+        device: format!("{} + {} CPU threads", gpu.device_name(), num_cpu_threads),
+        batch_size: gpu_batch_size,
+        attempts: total_attempts,
+        keys_per_sec,
+    }
+}
+
+/// Runs every CUDA device in `multi_gpu` simultaneously for `duration`
+/// against the unmatchable target, the same way `run_cuda_vanity_id_generator`
+/// fans a real search across every device, and reports their combined
+/// throughput as a single "CUDA (multi-GPU)" row.
+#[cfg(feature = "cuda")]
+pub fn benchmark_multi_cuda(
+    multi_gpu: crate::cuda_multi::MultiGpuVanityGenerator,
+    batch_size: u64,
+    duration: Duration,
+) -> BenchmarkResult {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Mutex};
+
+    let device_count = multi_gpu.device_count();
+    println!("Benchmarking CUDA multi-GPU ({} devices)...", device_count);
+
+    let criteria = MatchCriteria::unmatchable();
+    let found = Arc::new(AtomicBool::new(false));
+    let result = Arc::new(Mutex::new(None));
+    let next_batch = Arc::new(AtomicU64::new(0));
+    let start = Instant::now();
+
+    let (handles, device_attempts) = multi_gpu.spawn(
+        &criteria,
+        next_batch,
+        batch_size,
+        Arc::clone(&found),
+        Arc::clone(&result),
+    );
+
+    while start.elapsed() < duration {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    found.store(true, Ordering::Relaxed);
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let attempts: u64 = device_attempts.lock().unwrap().iter().sum();
+    let elapsed = start.elapsed().as_secs_f64();
+    let keys_per_sec = if elapsed > 0.0 {
+        attempts as f64 / elapsed
+    } else {
+        0.0
+    };
+
+    BenchmarkResult {
+        backend: "CUDA (multi-GPU)".to_string(),
+        device: format!("{} devices", device_count),
+        batch_size,
+        attempts,
+        keys_per_sec,
+    }
+}
+
+/// Prints the collected results as a simple aligned table.
+pub fn print_table(results: &[BenchmarkResult]) {
+    println!(
+        "\n{:<10} {:<32} {:>12} {:>16} {:>16}",
+        "Backend", "Device", "Batch Size", "Attempts", "Keys/sec"
+    );
+    println!("{}", "-".repeat(90));
+    for r in results {
+        println!(
+            "{:<10} {:<32} {:>12} {:>16} {:>16.0}",
+            r.backend, r.device, r.batch_size, r.attempts, r.keys_per_sec
+        );
+    }
+}
+
+/// Serializes `results` as a JSON array and writes them to `path`.
+pub fn write_json(results: &[BenchmarkResult], path: &str) -> std::io::Result<()> {
+    let body = results
+        .iter()
+        .map(|r| r.to_json())
+        .collect::<Vec<_>>()
+        .join(",");
+    std::fs::write(path, format!("[{}]", body))
+}
@@ -0,0 +1,69 @@
+//! Durable per-thread search progress for `--checkpoint`/`--resume`, so a
+//! long-running search for a rare prefix survives a restart instead of
+//! re-searching from the beginning (or, worse, silently overlapping with
+//! itself). Deliberately a flat line-oriented text file rather than a binary
+//! or serde-based format: the crate has no serialization dependency, and a
+//! checkpoint is just `num_threads` integers, not worth pulling one in for.
+
+use std::io::Write;
+
+/// The lowest un-searched counter for each thread, indexed by thread id.
+pub struct Checkpoint {
+    pub counters: Vec<u64>,
+}
+
+impl Checkpoint {
+    /// Reads `path`, expecting one `<thread_id> <counter>` pair per line.
+    /// Fails if the file doesn't have an entry for every thread in
+    /// `0..num_threads`, or has one for a thread outside it — a stale checkpoint from a run with a different
+    /// thread count can't be resumed safely.
+    pub fn load(path: &str, num_threads: usize) -> Result<Self, String> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+
+        let mut counters = vec![None; num_threads];
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let thread_id: usize = parts
+                .next()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(|| format!("malformed checkpoint line: {:?}", line))?;
+            let counter: u64 = parts
+                .next()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(|| format!("malformed checkpoint line: {:?}", line))?;
+            match counters.get_mut(thread_id) {
+                Some(slot) => *slot = Some(counter),
+                None => {
+                    return Err(format!(
+                        "{} has an entry for thread {} but only {} threads are running",
+                        path, thread_id, num_threads
+                    ))
+                }
+            }
+        }
+
+        let counters: Option<Vec<u64>> = counters.into_iter().collect();
+        counters
+            .ok_or_else(|| format!("{} is missing an entry for one or more of {} threads", path, num_threads))
+            .map(|counters| Checkpoint { counters })
+    }
+
+    /// Overwrites `path` with the current counters. Writes to a temp file
+    /// first and renames over the target so a crash mid-write can't leave a
+    /// truncated checkpoint behind.
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let tmp_path = format!("{}.tmp", path);
+        {
+            let mut file = std::fs::File::create(&tmp_path)?;
+            for (thread_id, counter) in self.counters.iter().enumerate() {
+                writeln!(file, "{} {}", thread_id, counter)?;
+            }
+        }
+        std::fs::rename(&tmp_path, path)
+    }
+}
@@ -1,122 +1,1094 @@
-use std::ffi::{CStr, CString};
+//! CUDA support implemented entirely against the *driver* API, loaded at
+//! runtime via `dlopen`/`LoadLibrary` (through the `libloading` crate)
+//! instead of being linked against `libcuda`/`libcudart` at build time. This
+//! means the binary is the same whether or not the machine that built it had
+//! the CUDA toolkit installed: it simply finds no driver and reports
+//! `CudaVanityGenerator::new()` as unavailable.
+//!
+//! The device kernel itself is compiled to PTX by build.rs (or shipped
+//! prebuilt) and JIT-loaded into a context via `cuModuleLoadData`.
+
+use crate::backend::VanityBackend;
+use crate::match_criteria::MatchCriteria;
+use libloading::{Library, Symbol};
+use std::ffi::{c_void, CStr, CString};
 use std::os::raw::{c_char, c_int};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::OnceLock;
 
-// FFI declarations for CUDA functions
-extern "C" {
-    fn cuda_init(
-        max_threads_per_block: *mut c_int,
-        device_name: *mut c_char,
-        name_len: c_int,
-    ) -> c_int;
-    fn cuda_search_vanity_id(
-        prefix: *const c_char,
-        prefix_len: c_int,
-        start_counter: u64,
-        batch_size: u64,
-        results: *mut u32,
-    ) -> c_int;
-    fn cuda_cleanup();
+type CUresult = c_int;
+type CUdevice = c_int;
+type CUcontext = *mut c_void;
+type CUmodule = *mut c_void;
+type CUfunction = *mut c_void;
+type CUdeviceptr = u64;
+type CUstream = *mut c_void;
+type CUevent = *mut c_void;
+
+const CUDA_SUCCESS: CUresult = 0;
+const CUDA_ERROR_NOT_READY: CUresult = 600;
+const CU_DEVICE_ATTRIBUTE_MAX_THREADS_PER_BLOCK: c_int = 1;
+const CU_DEVICE_ATTRIBUTE_CAN_MAP_HOST_MEMORY: c_int = 19;
+const CU_MEMHOSTALLOC_DEVICEMAP: u32 = 0x02;
+
+/// Number of `u32` words in the kernel's results buffer: `[found_flag,
+/// counter_low, counter_high, key_data_as_8_u32s]`.
+const RESULTS_WORDS: usize = 11;
+
+// PTX for the device kernel, embedded at compile time. Empty when no PTX
+// could be produced or found (see build.rs) — treated as "CUDA unavailable".
+static PTX_SOURCE: &str = include_str!(env!("VANITY_PTX_PATH"));
+
+/// A CUDA driver failure, carrying the numeric `CUresult`, its symbolic name
+/// (e.g. `CUDA_ERROR_OUT_OF_MEMORY`), and the human-readable description, so
+/// callers can distinguish out-of-memory, invalid-device, and launch
+/// failures instead of treating every failure identically.
+#[derive(Debug, Clone)]
+pub struct CudaError {
+    pub code: i32,
+    pub name: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for CudaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}): {}", self.name, self.code, self.message)
+    }
+}
+
+impl std::error::Error for CudaError {}
+
+macro_rules! load_symbols {
+    ($lib:expr, { $($field:ident : $ty:ty = $name:literal),+ $(,)? }) => {
+        Driver {
+            $(
+                $field: unsafe {
+                    let sym: Symbol<$ty> = $lib.get(concat!($name, "\0").as_bytes())
+                        .map_err(|e| format!("missing CUDA driver symbol {}: {}", $name, e))?;
+                    *sym
+                },
+            )+
+            _lib: $lib,
+        }
+    };
+}
+
+#[allow(non_snake_case)]
+struct Driver {
+    _lib: Library,
+    cuInit: unsafe extern "C" fn(u32) -> CUresult,
+    cuDeviceGetCount: unsafe extern "C" fn(*mut c_int) -> CUresult,
+    cuDeviceGet: unsafe extern "C" fn(*mut CUdevice, c_int) -> CUresult,
+    cuDeviceGetName: unsafe extern "C" fn(*mut c_char, c_int, CUdevice) -> CUresult,
+    cuDeviceGetAttribute: unsafe extern "C" fn(*mut c_int, c_int, CUdevice) -> CUresult,
+    cuDeviceComputeCapability: unsafe extern "C" fn(*mut c_int, *mut c_int, CUdevice) -> CUresult,
+    cuDeviceTotalMem_v2: unsafe extern "C" fn(*mut usize, CUdevice) -> CUresult,
+    cuCtxCreate_v2: unsafe extern "C" fn(*mut CUcontext, u32, CUdevice) -> CUresult,
+    cuCtxDestroy_v2: unsafe extern "C" fn(CUcontext) -> CUresult,
+    cuCtxSetCurrent: unsafe extern "C" fn(CUcontext) -> CUresult,
+    cuCtxSynchronize: unsafe extern "C" fn() -> CUresult,
+    cuMemGetInfo_v2: unsafe extern "C" fn(*mut usize, *mut usize) -> CUresult,
+    cuModuleLoadData: unsafe extern "C" fn(*mut CUmodule, *const c_void) -> CUresult,
+    cuModuleGetFunction: unsafe extern "C" fn(*mut CUfunction, CUmodule, *const c_char) -> CUresult,
+    cuMemAlloc_v2: unsafe extern "C" fn(*mut CUdeviceptr, usize) -> CUresult,
+    cuMemFree_v2: unsafe extern "C" fn(CUdeviceptr) -> CUresult,
+    cuMemcpyHtoD_v2: unsafe extern "C" fn(CUdeviceptr, *const c_void, usize) -> CUresult,
+    cuMemcpyDtoH_v2: unsafe extern "C" fn(*mut c_void, CUdeviceptr, usize) -> CUresult,
+    cuMemsetD8_v2: unsafe extern "C" fn(CUdeviceptr, u8, usize) -> CUresult,
+    cuLaunchKernel: unsafe extern "C" fn(
+        CUfunction,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        u32,
+        *mut c_void,
+        *mut *mut c_void,
+        *mut *mut c_void,
+    ) -> CUresult,
+    cuGetErrorName: unsafe extern "C" fn(CUresult, *mut *const c_char) -> CUresult,
+    cuGetErrorString: unsafe extern "C" fn(CUresult, *mut *const c_char) -> CUresult,
+    cuStreamCreate: unsafe extern "C" fn(*mut CUstream, u32) -> CUresult,
+    cuStreamDestroy_v2: unsafe extern "C" fn(CUstream) -> CUresult,
+    cuStreamQuery: unsafe extern "C" fn(CUstream) -> CUresult,
+    cuStreamSynchronize: unsafe extern "C" fn(CUstream) -> CUresult,
+    cuEventCreate: unsafe extern "C" fn(*mut CUevent, u32) -> CUresult,
+    cuEventDestroy_v2: unsafe extern "C" fn(CUevent) -> CUresult,
+    cuEventRecord: unsafe extern "C" fn(CUevent, CUstream) -> CUresult,
+    cuEventElapsedTime: unsafe extern "C" fn(*mut f32, CUevent, CUevent) -> CUresult,
+    cuMemcpyHtoDAsync_v2:
+        unsafe extern "C" fn(CUdeviceptr, *const c_void, usize, CUstream) -> CUresult,
+    cuMemcpyDtoHAsync_v2:
+        unsafe extern "C" fn(*mut c_void, CUdeviceptr, usize, CUstream) -> CUresult,
+    cuMemsetD8Async: unsafe extern "C" fn(CUdeviceptr, u8, usize, CUstream) -> CUresult,
+    cuMemHostAlloc: unsafe extern "C" fn(*mut *mut c_void, usize, u32) -> CUresult,
+    cuMemFreeHost: unsafe extern "C" fn(*mut c_void) -> CUresult,
+    cuMemHostGetDevicePointer_v2:
+        unsafe extern "C" fn(*mut CUdeviceptr, *mut c_void, u32) -> CUresult,
+}
+
+fn driver_library_names() -> &'static [&'static str] {
+    if cfg!(target_os = "windows") {
+        &["nvcuda.dll"]
+    } else if cfg!(target_os = "macos") {
+        &["libcuda.dylib"]
+    } else {
+        &["libcuda.so", "libcuda.so.1"]
+    }
+}
+
+fn load_driver() -> Result<Driver, String> {
+    let mut last_err = "no CUDA driver library names configured".to_string();
+    for name in driver_library_names() {
+        match unsafe { Library::new(name) } {
+            Ok(lib) => {
+                let driver: Result<Driver, String> = (|| {
+                    Ok(load_symbols!(lib, {
+                        cuInit: unsafe extern "C" fn(u32) -> CUresult = "cuInit",
+                        cuDeviceGetCount: unsafe extern "C" fn(*mut c_int) -> CUresult = "cuDeviceGetCount",
+                        cuDeviceGet: unsafe extern "C" fn(*mut CUdevice, c_int) -> CUresult = "cuDeviceGet",
+                        cuDeviceGetName: unsafe extern "C" fn(*mut c_char, c_int, CUdevice) -> CUresult = "cuDeviceGetName",
+                        cuDeviceGetAttribute: unsafe extern "C" fn(*mut c_int, c_int, CUdevice) -> CUresult = "cuDeviceGetAttribute",
+                        cuDeviceComputeCapability: unsafe extern "C" fn(*mut c_int, *mut c_int, CUdevice) -> CUresult = "cuDeviceComputeCapability",
+                        cuDeviceTotalMem_v2: unsafe extern "C" fn(*mut usize, CUdevice) -> CUresult = "cuDeviceTotalMem_v2",
+                        cuCtxCreate_v2: unsafe extern "C" fn(*mut CUcontext, u32, CUdevice) -> CUresult = "cuCtxCreate_v2",
+                        cuCtxDestroy_v2: unsafe extern "C" fn(CUcontext) -> CUresult = "cuCtxDestroy_v2",
+                        cuCtxSetCurrent: unsafe extern "C" fn(CUcontext) -> CUresult = "cuCtxSetCurrent",
+                        cuCtxSynchronize: unsafe extern "C" fn() -> CUresult = "cuCtxSynchronize",
+                        cuMemGetInfo_v2: unsafe extern "C" fn(*mut usize, *mut usize) -> CUresult = "cuMemGetInfo_v2",
+                        cuModuleLoadData: unsafe extern "C" fn(*mut CUmodule, *const c_void) -> CUresult = "cuModuleLoadData",
+                        cuModuleGetFunction: unsafe extern "C" fn(*mut CUfunction, CUmodule, *const c_char) -> CUresult = "cuModuleGetFunction",
+                        cuMemAlloc_v2: unsafe extern "C" fn(*mut CUdeviceptr, usize) -> CUresult = "cuMemAlloc_v2",
+                        cuMemFree_v2: unsafe extern "C" fn(CUdeviceptr) -> CUresult = "cuMemFree_v2",
+                        cuMemcpyHtoD_v2: unsafe extern "C" fn(CUdeviceptr, *const c_void, usize) -> CUresult = "cuMemcpyHtoD_v2",
+                        cuMemcpyDtoH_v2: unsafe extern "C" fn(*mut c_void, CUdeviceptr, usize) -> CUresult = "cuMemcpyDtoH_v2",
+                        cuMemsetD8_v2: unsafe extern "C" fn(CUdeviceptr, u8, usize) -> CUresult = "cuMemsetD8_v2",
+                        cuLaunchKernel: unsafe extern "C" fn(CUfunction, u32, u32, u32, u32, u32, u32, u32, *mut c_void, *mut *mut c_void, *mut *mut c_void) -> CUresult = "cuLaunchKernel",
+                        cuGetErrorName: unsafe extern "C" fn(CUresult, *mut *const c_char) -> CUresult = "cuGetErrorName",
+                        cuGetErrorString: unsafe extern "C" fn(CUresult, *mut *const c_char) -> CUresult = "cuGetErrorString",
+                        cuStreamCreate: unsafe extern "C" fn(*mut CUstream, u32) -> CUresult = "cuStreamCreate",
+                        cuStreamDestroy_v2: unsafe extern "C" fn(CUstream) -> CUresult = "cuStreamDestroy_v2",
+                        cuStreamQuery: unsafe extern "C" fn(CUstream) -> CUresult = "cuStreamQuery",
+                        cuStreamSynchronize: unsafe extern "C" fn(CUstream) -> CUresult = "cuStreamSynchronize",
+                        cuEventCreate: unsafe extern "C" fn(*mut CUevent, u32) -> CUresult = "cuEventCreate",
+                        cuEventDestroy_v2: unsafe extern "C" fn(CUevent) -> CUresult = "cuEventDestroy_v2",
+                        cuEventRecord: unsafe extern "C" fn(CUevent, CUstream) -> CUresult = "cuEventRecord",
+                        cuEventElapsedTime: unsafe extern "C" fn(*mut f32, CUevent, CUevent) -> CUresult = "cuEventElapsedTime",
+                        cuMemcpyHtoDAsync_v2: unsafe extern "C" fn(CUdeviceptr, *const c_void, usize, CUstream) -> CUresult = "cuMemcpyHtoDAsync_v2",
+                        cuMemcpyDtoHAsync_v2: unsafe extern "C" fn(*mut c_void, CUdeviceptr, usize, CUstream) -> CUresult = "cuMemcpyDtoHAsync_v2",
+                        cuMemsetD8Async: unsafe extern "C" fn(CUdeviceptr, u8, usize, CUstream) -> CUresult = "cuMemsetD8Async",
+                        cuMemHostAlloc: unsafe extern "C" fn(*mut *mut c_void, usize, u32) -> CUresult = "cuMemHostAlloc",
+                        cuMemFreeHost: unsafe extern "C" fn(*mut c_void) -> CUresult = "cuMemFreeHost",
+                        cuMemHostGetDevicePointer_v2: unsafe extern "C" fn(*mut CUdeviceptr, *mut c_void, u32) -> CUresult = "cuMemHostGetDevicePointer_v2",
+                    }))
+                })();
+
+                match driver {
+                    Ok(driver) => {
+                        let init = unsafe { (driver.cuInit)(0) };
+                        if init != CUDA_SUCCESS {
+                            last_err = format!("cuInit failed with code {}", init);
+                            continue;
+                        }
+                        return Ok(driver);
+                    }
+                    Err(e) => last_err = e,
+                }
+            }
+            Err(e) => last_err = format!("failed to load {}: {}", name, e),
+        }
+    }
+    Err(last_err)
+}
+
+fn driver() -> Result<&'static Driver, String> {
+    static DRIVER: OnceLock<Result<Driver, String>> = OnceLock::new();
+    DRIVER
+        .get_or_init(load_driver)
+        .as_ref()
+        .map_err(|e| e.clone())
+}
+
+fn cuda_error(driver: &Driver, result: CUresult, context: &str) -> Box<dyn std::error::Error> {
+    let mut name_ptr: *const c_char = std::ptr::null();
+    let mut message_ptr: *const c_char = std::ptr::null();
+    unsafe {
+        (driver.cuGetErrorName)(result, &mut name_ptr);
+        (driver.cuGetErrorString)(result, &mut message_ptr);
+    }
+    let name = if name_ptr.is_null() {
+        "UNKNOWN".to_string()
+    } else {
+        unsafe { CStr::from_ptr(name_ptr).to_string_lossy().into_owned() }
+    };
+    let message = if message_ptr.is_null() {
+        "no description available".to_string()
+    } else {
+        unsafe { CStr::from_ptr(message_ptr).to_string_lossy().into_owned() }
+    };
+
+    Box::new(CudaError {
+        code: result,
+        name,
+        message: format!("{}: {}", context, message),
+    })
+}
+
+/// Static metadata about a CUDA device, as reported by `list_devices()`.
+#[derive(Debug, Clone)]
+pub struct DeviceInfo {
+    pub device_id: i32,
+    pub name: String,
+    pub compute_capability: (i32, i32),
+    pub total_memory_bytes: usize,
+    pub free_memory_bytes: usize,
+    pub max_threads_per_block: i32,
+}
+
+/// Selection policy for `CudaVanityGenerator::new_with_policy`, exposed on
+/// the CLI as `--cuda-device`/`--cuda-device-policy`.
+pub enum DevicePolicy {
+    /// Bind to a specific device index.
+    Index(i32),
+    /// Pick the device with the highest `major*10 + minor` compute
+    /// capability, breaking ties by free memory.
+    HighestCompute,
+    /// Pick the device reporting the most free global memory.
+    MostFreeMemory,
+}
+
+/// Devices reporting less than this much free memory are skipped by
+/// `HighestCompute`/`MostFreeMemory` selection.
+pub const DEFAULT_MIN_FREE_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+
+/// How the host thread should wait on the CUDA context, passed to
+/// `cuCtxCreate_v2`'s flags (the driver-API equivalent of
+/// `cudaSetDeviceFlags`).
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ScheduleMode {
+    /// Let the driver pick (spins if there's only one context, otherwise
+    /// yields). The driver's own default.
+    #[default]
+    Auto,
+    /// Busy-wait for GPU work to finish. Lowest latency, but pins a host
+    /// core at 100% — avoid on shared machines.
+    Spin,
+    /// Yield the host thread while waiting. Higher latency than `Spin` but
+    /// frees the core for other work.
+    Yield,
+    /// Block the host thread on a synchronization primitive while waiting.
+    /// Highest latency, lowest CPU usage.
+    BlockingSync,
+}
+
+impl ScheduleMode {
+    fn as_ctx_flag(self) -> u32 {
+        match self {
+            ScheduleMode::Auto => 0x00,
+            ScheduleMode::Spin => 0x01,
+            ScheduleMode::Yield => 0x02,
+            ScheduleMode::BlockingSync => 0x04,
+        }
+    }
+}
+
+/// Tuning knobs for `CudaVanityGenerator::new_with_config`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CudaConfig {
+    pub schedule: ScheduleMode,
+    /// Allocate the results buffer as pinned, mapped host memory and read the
+    /// found key directly out of it instead of issuing a `cuMemcpyDtoH_v2`
+    /// after the kernel completes. Worthwhile on integrated GPUs that already
+    /// share host memory; ignored, with a notice printed (falls back to an
+    /// explicit copy), on devices that report
+    /// `CU_DEVICE_ATTRIBUTE_CAN_MAP_HOST_MEMORY` as unsupported.
+    pub zero_copy: bool,
+}
+
+/// Returns the number of CUDA-capable devices visible to the process, or 0 if
+/// none are found or the driver can't be loaded.
+pub fn device_count() -> usize {
+    let Ok(driver) = driver() else {
+        return 0;
+    };
+    let mut count: c_int = 0;
+    let result = unsafe { (driver.cuDeviceGetCount)(&mut count) };
+    if result != CUDA_SUCCESS || count < 0 {
+        0
+    } else {
+        count as usize
+    }
+}
+
+/// Lists every CUDA device visible to the process along with its compute
+/// capability and memory, skipping devices whose properties can't be read.
+pub fn list_devices() -> Vec<DeviceInfo> {
+    let Ok(driver) = driver() else {
+        return Vec::new();
+    };
+
+    let mut devices = Vec::new();
+    for ordinal in 0..device_count() as c_int {
+        let mut device: CUdevice = 0;
+        if unsafe { (driver.cuDeviceGet)(&mut device, ordinal) } != CUDA_SUCCESS {
+            continue;
+        }
+
+        let mut name_buffer = [0u8; 256];
+        if unsafe {
+            (driver.cuDeviceGetName)(
+                name_buffer.as_mut_ptr() as *mut c_char,
+                name_buffer.len() as c_int,
+                device,
+            )
+        } != CUDA_SUCCESS
+        {
+            continue;
+        }
+        let name = unsafe {
+            CStr::from_ptr(name_buffer.as_ptr() as *const c_char)
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        let mut major: c_int = 0;
+        let mut minor: c_int = 0;
+        unsafe { (driver.cuDeviceComputeCapability)(&mut major, &mut minor, device) };
+
+        let mut total_mem: usize = 0;
+        unsafe { (driver.cuDeviceTotalMem_v2)(&mut total_mem, device) };
+
+        let mut max_threads_per_block: c_int = 0;
+        unsafe {
+            (driver.cuDeviceGetAttribute)(
+                &mut max_threads_per_block,
+                CU_DEVICE_ATTRIBUTE_MAX_THREADS_PER_BLOCK,
+                device,
+            )
+        };
+
+        // Free memory is only meaningful for the currently-current context;
+        // bind one briefly to read it, then tear it down.
+        let mut free_mem = total_mem;
+        let mut ctx: CUcontext = std::ptr::null_mut();
+        if unsafe { (driver.cuCtxCreate_v2)(&mut ctx, 0, device) } == CUDA_SUCCESS {
+            let mut total = 0usize;
+            unsafe { (driver.cuMemGetInfo_v2)(&mut free_mem, &mut total) };
+            unsafe { (driver.cuCtxDestroy_v2)(ctx) };
+        }
+
+        devices.push(DeviceInfo {
+            device_id: ordinal,
+            name,
+            compute_capability: (major, minor),
+            total_memory_bytes: total_mem,
+            free_memory_bytes: free_mem,
+            max_threads_per_block,
+        });
+    }
+
+    devices
+}
+
+/// The kernel's results buffer, allocated either as a plain device
+/// allocation (read back via an explicit `cuMemcpyDtoH_v2`) or, when
+/// `zero_copy` is requested and supported, as pinned host memory mapped into
+/// the device's address space, read directly with no copy.
+struct ResultsBuffer {
+    device_ptr: CUdeviceptr,
+    host_ptr: *mut c_void,
+}
+
+impl ResultsBuffer {
+    fn alloc(driver: &Driver, zero_copy: bool) -> Result<Self, CUresult> {
+        let bytes = RESULTS_WORDS * std::mem::size_of::<u32>();
+        if zero_copy {
+            let mut host_ptr: *mut c_void = std::ptr::null_mut();
+            let ret =
+                unsafe { (driver.cuMemHostAlloc)(&mut host_ptr, bytes, CU_MEMHOSTALLOC_DEVICEMAP) };
+            if ret != CUDA_SUCCESS {
+                return Err(ret);
+            }
+            unsafe { std::ptr::write_bytes(host_ptr as *mut u8, 0, bytes) };
+
+            let mut device_ptr: CUdeviceptr = 0;
+            let ret =
+                unsafe { (driver.cuMemHostGetDevicePointer_v2)(&mut device_ptr, host_ptr, 0) };
+            if ret != CUDA_SUCCESS {
+                unsafe { (driver.cuMemFreeHost)(host_ptr) };
+                return Err(ret);
+            }
+            Ok(ResultsBuffer {
+                device_ptr,
+                host_ptr,
+            })
+        } else {
+            let mut device_ptr: CUdeviceptr = 0;
+            let ret = unsafe { (driver.cuMemAlloc_v2)(&mut device_ptr, bytes) };
+            if ret != CUDA_SUCCESS {
+                return Err(ret);
+            }
+            let ret = unsafe { (driver.cuMemsetD8_v2)(device_ptr, 0, bytes) };
+            if ret != CUDA_SUCCESS {
+                unsafe { (driver.cuMemFree_v2)(device_ptr) };
+                return Err(ret);
+            }
+            Ok(ResultsBuffer {
+                device_ptr,
+                host_ptr: std::ptr::null_mut(),
+            })
+        }
+    }
+
+    fn is_zero_copy(&self) -> bool {
+        !self.host_ptr.is_null()
+    }
+
+    /// Reads the results back, synchronously copying from the device unless
+    /// this buffer is zero-copy (in which case the host pointer already
+    /// holds whatever the kernel wrote, once the stream/context is
+    /// synchronized).
+    fn read(&self, driver: &Driver) -> Result<[u32; RESULTS_WORDS], CUresult> {
+        let mut results = [0u32; RESULTS_WORDS];
+        if self.is_zero_copy() {
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    self.host_ptr as *const u32,
+                    results.as_mut_ptr(),
+                    RESULTS_WORDS,
+                )
+            };
+        } else {
+            let bytes = RESULTS_WORDS * std::mem::size_of::<u32>();
+            let ret = unsafe {
+                (driver.cuMemcpyDtoH_v2)(
+                    results.as_mut_ptr() as *mut c_void,
+                    self.device_ptr,
+                    bytes,
+                )
+            };
+            if ret != CUDA_SUCCESS {
+                return Err(ret);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Resets the buffer to zero for the next batch, without a fresh
+    /// alloc/free round trip.
+    fn reset(&self, driver: &Driver) -> CUresult {
+        if self.is_zero_copy() {
+            unsafe {
+                std::ptr::write_bytes(
+                    self.host_ptr as *mut u8,
+                    0,
+                    RESULTS_WORDS * std::mem::size_of::<u32>(),
+                )
+            };
+            CUDA_SUCCESS
+        } else {
+            unsafe {
+                (driver.cuMemsetD8_v2)(
+                    self.device_ptr,
+                    0,
+                    RESULTS_WORDS * std::mem::size_of::<u32>(),
+                )
+            }
+        }
+    }
+
+    fn free(&self, driver: &Driver) {
+        unsafe {
+            if self.is_zero_copy() {
+                (driver.cuMemFreeHost)(self.host_ptr);
+            } else {
+                (driver.cuMemFree_v2)(self.device_ptr);
+            }
+        }
+    }
 }
 
 pub struct CudaVanityGenerator {
-    max_threads_per_block: i32,
+    device_id: i32,
     device_name: String,
+    max_threads_per_block: i32,
+    context: CUcontext,
+    module: CUmodule,
+    function: CUfunction,
+    zero_copy: bool,
 }
 
+// The context/module/function handles are only ever touched while holding
+// `&self`/`&mut self` on the owning thread; `CudaVanityGenerator` is not
+// `Sync`, but is safe to move to another thread (as `MultiGpuVanityGenerator`
+// does) and use there.
+unsafe impl Send for CudaVanityGenerator {}
+
 impl CudaVanityGenerator {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let mut max_threads_per_block: c_int = 0;
-        let mut device_name_buffer = [0u8; 256];
+        Self::new_on_device(0)
+    }
+
+    /// Binds to a specific CUDA device index (as reported by `device_count()`)
+    /// instead of always using device 0.
+    pub fn new_on_device(device_id: i32) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::new_with_config(device_id, CudaConfig::default())
+    }
+
+    /// Binds to `device_id` with explicit scheduling and zero-copy tuning.
+    /// `config.zero_copy` is dropped with a printed notice (falling back to
+    /// the normal explicit-copy path) on devices that don't report
+    /// `CU_DEVICE_ATTRIBUTE_CAN_MAP_HOST_MEMORY`.
+    pub fn new_with_config(
+        device_id: i32,
+        config: CudaConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        if PTX_SOURCE.is_empty() {
+            return Err("No CUDA PTX kernel was compiled or shipped with this build.".into());
+        }
+
+        let driver = driver().map_err(|e| format!("CUDA driver unavailable: {}", e))?;
 
-        // Initialize CUDA
+        let mut device: CUdevice = 0;
+        let result = unsafe { (driver.cuDeviceGet)(&mut device, device_id) };
+        if result != CUDA_SUCCESS {
+            return Err(cuda_error(driver, result, "Failed to get CUDA device"));
+        }
+
+        let mut name_buffer = [0u8; 256];
         let result = unsafe {
-            cuda_init(
+            (driver.cuDeviceGetName)(
+                name_buffer.as_mut_ptr() as *mut c_char,
+                name_buffer.len() as c_int,
+                device,
+            )
+        };
+        if result != CUDA_SUCCESS {
+            return Err(cuda_error(driver, result, "Failed to get CUDA device name"));
+        }
+        let device_name = unsafe {
+            CStr::from_ptr(name_buffer.as_ptr() as *const c_char)
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        let mut max_threads_per_block: c_int = 0;
+        unsafe {
+            (driver.cuDeviceGetAttribute)(
                 &mut max_threads_per_block,
-                device_name_buffer.as_mut_ptr() as *mut c_char,
-                device_name_buffer.len() as c_int,
+                CU_DEVICE_ATTRIBUTE_MAX_THREADS_PER_BLOCK,
+                device,
             )
         };
 
-        match result {
-            0 => {
-                // Success - extract device name
-                let device_name = unsafe {
-                    CStr::from_ptr(device_name_buffer.as_ptr() as *const c_char)
-                        .to_string_lossy()
-                        .into_owned()
-                };
+        let mut can_map_host_memory: c_int = 0;
+        unsafe {
+            (driver.cuDeviceGetAttribute)(
+                &mut can_map_host_memory,
+                CU_DEVICE_ATTRIBUTE_CAN_MAP_HOST_MEMORY,
+                device,
+            )
+        };
+        let zero_copy = config.zero_copy && can_map_host_memory != 0;
+        if config.zero_copy && !zero_copy {
+            println!(
+                "CUDA device {} does not support mapped host memory; falling back to explicit result copies.",
+                device_id
+            );
+        }
+
+        let mut context: CUcontext = std::ptr::null_mut();
+        let result =
+            unsafe { (driver.cuCtxCreate_v2)(&mut context, config.schedule.as_ctx_flag(), device) };
+        if result != CUDA_SUCCESS {
+            return Err(cuda_error(driver, result, "Failed to create CUDA context"));
+        }
 
-                println!("Using CUDA GPU: {}", device_name);
+        let ptx_cstring = CString::new(PTX_SOURCE)?;
+        let mut module: CUmodule = std::ptr::null_mut();
+        let result = unsafe {
+            (driver.cuModuleLoadData)(&mut module, ptx_cstring.as_ptr() as *const c_void)
+        };
+        if result != CUDA_SUCCESS {
+            unsafe { (driver.cuCtxDestroy_v2)(context) };
+            return Err(cuda_error(driver, result, "Failed to load CUDA module"));
+        }
 
-                Ok(CudaVanityGenerator {
-                    max_threads_per_block,
-                    device_name,
-                })
+        let function_name = CString::new("vanity_search_kernel").unwrap();
+        let mut function: CUfunction = std::ptr::null_mut();
+        let result =
+            unsafe { (driver.cuModuleGetFunction)(&mut function, module, function_name.as_ptr()) };
+        if result != CUDA_SUCCESS {
+            unsafe { (driver.cuCtxDestroy_v2)(context) };
+            return Err(cuda_error(
+                driver,
+                result,
+                "Failed to find vanity_search_kernel",
+            ));
+        }
+
+        println!("Using CUDA GPU {}: {}", device_id, device_name);
+
+        Ok(CudaVanityGenerator {
+            device_id,
+            device_name,
+            max_threads_per_block,
+            context,
+            module,
+            function,
+            zero_copy,
+        })
+    }
+
+    /// Chooses a device using `policy` instead of always binding device 0.
+    /// `HighestCompute`/`MostFreeMemory` skip devices below
+    /// `DEFAULT_MIN_FREE_MEMORY_BYTES` of free memory.
+    pub fn new_with_policy(policy: DevicePolicy) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::new_with_policy_and_config(policy, CudaConfig::default())
+    }
+
+    /// Combines `new_with_policy`'s device selection with `new_with_config`'s
+    /// scheduling/zero-copy tuning.
+    pub fn new_with_policy_and_config(
+        policy: DevicePolicy,
+        config: CudaConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        match policy {
+            DevicePolicy::Index(n) => Self::new_with_config(n, config),
+            DevicePolicy::HighestCompute => {
+                let devices = list_devices();
+                let chosen = devices
+                    .into_iter()
+                    .filter(|d| d.free_memory_bytes >= DEFAULT_MIN_FREE_MEMORY_BYTES)
+                    .max_by_key(|d| {
+                        let (major, minor) = d.compute_capability;
+                        (major * 10 + minor, d.free_memory_bytes)
+                    })
+                    .ok_or("No CUDA device meets the minimum free-memory threshold.")?;
+                Self::new_with_config(chosen.device_id, config)
             }
-            -1 => {
-                Err("No CUDA devices found. This requires an NVIDIA GPU with CUDA support.".into())
+            DevicePolicy::MostFreeMemory => {
+                let devices = list_devices();
+                let chosen = devices
+                    .into_iter()
+                    .filter(|d| d.free_memory_bytes >= DEFAULT_MIN_FREE_MEMORY_BYTES)
+                    .max_by_key(|d| d.free_memory_bytes)
+                    .ok_or("No CUDA device meets the minimum free-memory threshold.")?;
+                Self::new_with_config(chosen.device_id, config)
             }
-            -2 => Err("Failed to get CUDA device properties.".into()),
-            -3 => Err("Failed to set CUDA device.".into()),
-            -4 => Err("Failed to allocate CUDA results buffer.".into()),
-            -5 => Err("Failed to allocate CUDA prefix buffer.".into()),
-            _ => Err(format!("Unknown CUDA initialization error: {}", result).into()),
         }
     }
 
+    pub fn device_id(&self) -> i32 {
+        self.device_id
+    }
+
     pub fn search_vanity_id(
         &self,
-        prefix: &str,
+        criteria: &MatchCriteria,
         start_counter: u64,
         batch_size: u64,
     ) -> Result<Option<(u64, [u8; 32])>, Box<dyn std::error::Error>> {
-        let prefix_cstring = CString::new(prefix)?;
-        let prefix_len = prefix.len() as c_int;
+        let driver = driver().map_err(|e| format!("CUDA driver unavailable: {}", e))?;
+
+        let result = unsafe { (driver.cuCtxSetCurrent)(self.context) };
+        if result != CUDA_SUCCESS {
+            return Err(cuda_error(
+                driver,
+                result,
+                "Failed to make CUDA context current",
+            ));
+        }
+
+        let spec = criteria.gpu_spec();
+        let spec_bytes = spec.len() * std::mem::size_of::<u32>();
+
+        let mut d_spec: CUdeviceptr = 0;
+        let result = unsafe { (driver.cuMemAlloc_v2)(&mut d_spec, spec_bytes) };
+        if result != CUDA_SUCCESS {
+            return Err(cuda_error(
+                driver,
+                result,
+                "Failed to allocate CUDA match-criteria buffer",
+            ));
+        }
+        let result = unsafe {
+            (driver.cuMemcpyHtoD_v2)(d_spec, spec.as_ptr() as *const c_void, spec_bytes)
+        };
+        if result != CUDA_SUCCESS {
+            unsafe { (driver.cuMemFree_v2)(d_spec) };
+            return Err(cuda_error(
+                driver,
+                result,
+                "Failed to copy match criteria to CUDA device",
+            ));
+        }
+
+        let results_buffer = ResultsBuffer::alloc(driver, self.zero_copy).map_err(|e| {
+            unsafe { (driver.cuMemFree_v2)(d_spec) };
+            cuda_error(driver, e, "Failed to allocate CUDA results buffer")
+        })?;
+
+        let threads_per_block = if self.max_threads_per_block > 0 {
+            self.max_threads_per_block.min(256) as u32
+        } else {
+            256
+        };
+        let blocks =
+            ((batch_size + threads_per_block as u64 - 1) / threads_per_block as u64) as u32;
 
-        // Create results buffer: [found_flag, counter_low, counter_high, key_data_as_8_u32s]
-        let mut results = [0u32; 11];
+        let mut params: [*mut c_void; 4] = [
+            &d_spec as *const _ as *mut c_void,
+            &start_counter as *const _ as *mut c_void,
+            &batch_size as *const _ as *mut c_void,
+            &results_buffer.device_ptr as *const _ as *mut c_void,
+        ];
 
         let result = unsafe {
-            cuda_search_vanity_id(
-                prefix_cstring.as_ptr(),
-                prefix_len,
-                start_counter,
-                batch_size,
-                results.as_mut_ptr(),
+            (driver.cuLaunchKernel)(
+                self.function,
+                blocks.max(1),
+                1,
+                1,
+                threads_per_block,
+                1,
+                1,
+                0,
+                std::ptr::null_mut(),
+                params.as_mut_ptr(),
+                std::ptr::null_mut(),
             )
         };
+        if result != CUDA_SUCCESS {
+            unsafe { (driver.cuMemFree_v2)(d_spec) };
+            results_buffer.free(driver);
+            return Err(cuda_error(driver, result, "CUDA kernel launch failed"));
+        }
+
+        let result = unsafe { (driver.cuCtxSynchronize)() };
+        if result != CUDA_SUCCESS {
+            unsafe { (driver.cuMemFree_v2)(d_spec) };
+            results_buffer.free(driver);
+            return Err(cuda_error(driver, result, "CUDA kernel execution failed"));
+        }
+
+        let results = results_buffer.read(driver);
+        unsafe { (driver.cuMemFree_v2)(d_spec) };
+        results_buffer.free(driver);
+        let results = results
+            .map_err(|e| cuda_error(driver, e, "Failed to copy results from CUDA device"))?;
 
-        match result {
-            0 => {
-                // Success - check if we found a match
-                let found_flag = results[0];
+        if results[0] == 0 {
+            return Ok(None);
+        }
+
+        let counter = results[1] as u64 | ((results[2] as u64) << 32);
+        let mut key_data = [0u8; 32];
+        for i in 0..8 {
+            let chunk = results[3 + i];
+            for j in 0..4 {
+                key_data[i * 4 + j] = ((chunk >> (j * 8)) & 0xFF) as u8;
+            }
+        }
 
-                if found_flag != 0 {
-                    // Reconstruct 64-bit counter from two 32-bit values
-                    let counter_low = results[1] as u64;
-                    let counter_high = results[2] as u64;
-                    let counter = counter_low | (counter_high << 32);
+        Ok(Some((counter, key_data)))
+    }
+
+    /// Pipelined variant of `search_vanity_id`: keeps `num_streams` batches
+    /// in flight at once so the next batch launches while the previous one
+    /// is still executing, instead of blocking per batch. `progress` is
+    /// called after each batch completes with the running total of counters
+    /// tried and the GPU-measured keys/sec for that batch (via CUDA event
+    /// timers, not CPU wall-clock).
+    ///
+    /// Batch starts are claimed from `next_batch` with `fetch_add`, the same
+    /// shared work-dispenser pattern `MultiGpuVanityGenerator::spawn` and the
+    /// hybrid CPU/GPU loops use, so this device can run alongside others
+    /// (or alongside CPU threads) against one counter space without either
+    /// side retrying counters the other already tried. `found` is checked
+    /// between batches so this call returns promptly once some other device
+    /// or thread has already won the race, instead of grinding through
+    /// `num_streams` more batches no one needs.
+    pub fn search_vanity_id_streamed(
+        &self,
+        criteria: &MatchCriteria,
+        next_batch: &AtomicU64,
+        batch_size: u64,
+        num_streams: usize,
+        found: &AtomicBool,
+        mut progress: impl FnMut(u64, f64),
+    ) -> Result<Option<(u64, [u8; 32])>, Box<dyn std::error::Error>> {
+        let driver = driver().map_err(|e| format!("CUDA driver unavailable: {}", e))?;
+        let num_streams = num_streams.max(1);
+
+        let result = unsafe { (driver.cuCtxSetCurrent)(self.context) };
+        if result != CUDA_SUCCESS {
+            return Err(cuda_error(
+                driver,
+                result,
+                "Failed to make CUDA context current",
+            ));
+        }
+
+        let spec = criteria.gpu_spec();
+        let spec_bytes = spec.len() * std::mem::size_of::<u32>();
+        let results_bytes = RESULTS_WORDS * std::mem::size_of::<u32>();
+
+        struct StreamSlot {
+            stream: CUstream,
+            start_event: CUevent,
+            stop_event: CUevent,
+            d_spec: CUdeviceptr,
+            results: ResultsBuffer,
+            batch_start: u64,
+            in_flight: bool,
+        }
+
+        let mut slots = Vec::with_capacity(num_streams);
+        let cleanup = |slots: &[StreamSlot]| {
+            for slot in slots {
+                unsafe {
+                    (driver.cuMemFree_v2)(slot.d_spec);
+                    (driver.cuEventDestroy_v2)(slot.start_event);
+                    (driver.cuEventDestroy_v2)(slot.stop_event);
+                    (driver.cuStreamDestroy_v2)(slot.stream);
+                }
+                slot.results.free(driver);
+            }
+        };
+
+        for _ in 0..num_streams {
+            let mut stream: CUstream = std::ptr::null_mut();
+            let mut start_event: CUevent = std::ptr::null_mut();
+            let mut stop_event: CUevent = std::ptr::null_mut();
+            let mut d_spec: CUdeviceptr = 0;
+
+            let ok = unsafe { (driver.cuStreamCreate)(&mut stream, 0) } == CUDA_SUCCESS
+                && unsafe { (driver.cuEventCreate)(&mut start_event, 0) } == CUDA_SUCCESS
+                && unsafe { (driver.cuEventCreate)(&mut stop_event, 0) } == CUDA_SUCCESS
+                && unsafe { (driver.cuMemAlloc_v2)(&mut d_spec, spec_bytes) } == CUDA_SUCCESS
+                && unsafe {
+                    (driver.cuMemcpyHtoDAsync_v2)(
+                        d_spec,
+                        spec.as_ptr() as *const c_void,
+                        spec_bytes,
+                        stream,
+                    )
+                } == CUDA_SUCCESS;
+
+            if !ok {
+                unsafe {
+                    (driver.cuMemFree_v2)(d_spec);
+                    (driver.cuEventDestroy_v2)(start_event);
+                    (driver.cuEventDestroy_v2)(stop_event);
+                    (driver.cuStreamDestroy_v2)(stream);
+                }
+                cleanup(&slots);
+                return Err("Failed to set up a CUDA stream for streamed search".into());
+            }
+
+            let results = match ResultsBuffer::alloc(driver, self.zero_copy) {
+                Ok(results) => results,
+                Err(e) => {
+                    unsafe {
+                        (driver.cuMemFree_v2)(d_spec);
+                        (driver.cuEventDestroy_v2)(start_event);
+                        (driver.cuEventDestroy_v2)(stop_event);
+                        (driver.cuStreamDestroy_v2)(stream);
+                    }
+                    cleanup(&slots);
+                    return Err(cuda_error(
+                        driver,
+                        e,
+                        "Failed to allocate CUDA results buffer",
+                    ));
+                }
+            };
+
+            slots.push(StreamSlot {
+                stream,
+                start_event,
+                stop_event,
+                d_spec,
+                results,
+                batch_start: 0,
+                in_flight: false,
+            });
+        }
+
+        let launch_batch = |slot: &mut StreamSlot, batch_start: u64| -> CUresult {
+            let mut ret = slot.results.reset(driver);
+            if ret != CUDA_SUCCESS {
+                return ret;
+            }
+
+            let threads_per_block = if self.max_threads_per_block > 0 {
+                self.max_threads_per_block.min(256) as u32
+            } else {
+                256
+            };
+            let blocks =
+                ((batch_size + threads_per_block as u64 - 1) / threads_per_block as u64) as u32;
+
+            let mut params: [*mut c_void; 4] = [
+                &slot.d_spec as *const _ as *mut c_void,
+                &batch_start as *const _ as *mut c_void,
+                &batch_size as *const _ as *mut c_void,
+                &slot.results.device_ptr as *const _ as *mut c_void,
+            ];
+
+            ret = unsafe { (driver.cuEventRecord)(slot.start_event, slot.stream) };
+            if ret != CUDA_SUCCESS {
+                return ret;
+            }
+
+            ret = unsafe {
+                (driver.cuLaunchKernel)(
+                    self.function,
+                    blocks.max(1),
+                    1,
+                    1,
+                    threads_per_block,
+                    1,
+                    1,
+                    0,
+                    slot.stream,
+                    params.as_mut_ptr(),
+                    std::ptr::null_mut(),
+                )
+            };
+            if ret != CUDA_SUCCESS {
+                return ret;
+            }
+
+            unsafe { (driver.cuEventRecord)(slot.stop_event, slot.stream) }
+        };
 
-                    // Reconstruct key data from the 8 u32 chunks
+        for slot in slots.iter_mut() {
+            let batch_start = next_batch.fetch_add(batch_size, Ordering::Relaxed);
+            let ret = launch_batch(slot, batch_start);
+            if ret != CUDA_SUCCESS {
+                cleanup(&slots);
+                return Err(cuda_error(
+                    driver,
+                    ret,
+                    "Failed to launch streamed CUDA batch",
+                ));
+            }
+            slot.batch_start = batch_start;
+            slot.in_flight = true;
+        }
+
+        let mut counters_tried = 0u64;
+        loop {
+            if found.load(Ordering::Relaxed) {
+                cleanup(&slots);
+                return Ok(None);
+            }
+
+            let mut made_progress = false;
+
+            for i in 0..slots.len() {
+                if !slots[i].in_flight {
+                    continue;
+                }
+
+                let query = unsafe { (driver.cuStreamQuery)(slots[i].stream) };
+                if query == CUDA_ERROR_NOT_READY {
+                    continue;
+                }
+                if query != CUDA_SUCCESS {
+                    cleanup(&slots);
+                    return Err(cuda_error(
+                        driver,
+                        query,
+                        "CUDA streamed kernel execution failed",
+                    ));
+                }
+
+                made_progress = true;
+
+                let results = if slots[i].results.is_zero_copy() {
+                    // The host pointer already reflects the kernel's writes
+                    // once its stream is known-idle (checked above), so no
+                    // copy is needed.
+                    slots[i].results.read(driver)
+                } else {
+                    let mut results = [0u32; RESULTS_WORDS];
+                    let ret = unsafe {
+                        (driver.cuMemcpyDtoHAsync_v2)(
+                            results.as_mut_ptr() as *mut c_void,
+                            slots[i].results.device_ptr,
+                            results_bytes,
+                            slots[i].stream,
+                        )
+                    };
+                    if ret == CUDA_SUCCESS {
+                        unsafe { (driver.cuStreamSynchronize)(slots[i].stream) };
+                        Ok(results)
+                    } else {
+                        Err(ret)
+                    }
+                };
+                let results = match results {
+                    Ok(results) => results,
+                    Err(e) => {
+                        cleanup(&slots);
+                        return Err(cuda_error(
+                            driver,
+                            e,
+                            "Failed to copy results from CUDA device",
+                        ));
+                    }
+                };
+
+                let mut elapsed_ms: f32 = 0.0;
+                unsafe {
+                    (driver.cuEventElapsedTime)(
+                        &mut elapsed_ms,
+                        slots[i].start_event,
+                        slots[i].stop_event,
+                    )
+                };
+                counters_tried += batch_size;
+                let keys_per_sec = if elapsed_ms > 0.0 {
+                    batch_size as f64 / (elapsed_ms as f64 / 1000.0)
+                } else {
+                    0.0
+                };
+                progress(counters_tried, keys_per_sec);
+
+                if results[0] != 0 {
+                    let counter = results[1] as u64 | ((results[2] as u64) << 32);
                     let mut key_data = [0u8; 32];
-                    for i in 0..8 {
-                        let chunk = results[3 + i];
+                    for w in 0..8 {
+                        let chunk = results[3 + w];
                         for j in 0..4 {
-                            key_data[i * 4 + j] = ((chunk >> (j * 8)) & 0xFF) as u8;
+                            key_data[w * 4 + j] = ((chunk >> (j * 8)) & 0xFF) as u8;
                         }
                     }
-
+                    cleanup(&slots);
                     return Ok(Some((counter, key_data)));
                 }
 
-                Ok(None)
+                let batch_start = next_batch.fetch_add(batch_size, Ordering::Relaxed);
+                let ret = launch_batch(&mut slots[i], batch_start);
+                if ret != CUDA_SUCCESS {
+                    cleanup(&slots);
+                    return Err(cuda_error(
+                        driver,
+                        ret,
+                        "Failed to launch streamed CUDA batch",
+                    ));
+                }
+                slots[i].batch_start = batch_start;
+            }
+
+            if !made_progress {
+                std::thread::yield_now();
             }
-            -1 => Err("CUDA not initialized.".into()),
-            -2 => Err("Failed to copy prefix to CUDA device.".into()),
-            -3 => Err("Failed to initialize CUDA results buffer.".into()),
-            -4 => Err("CUDA kernel execution failed.".into()),
-            -5 => Err("Failed to copy results from CUDA device.".into()),
-            _ => Err(format!("Unknown CUDA search error: {}", result).into()),
         }
     }
 
@@ -129,10 +1101,32 @@ impl CudaVanityGenerator {
     }
 }
 
+impl VanityBackend for CudaVanityGenerator {
+    fn search(
+        &self,
+        criteria: &MatchCriteria,
+        start_counter: u64,
+        batch_size: u64,
+    ) -> Result<Option<(u64, [u8; 32])>, Box<dyn std::error::Error>> {
+        self.search_vanity_id(criteria, start_counter, batch_size)
+    }
+
+    fn device_name(&self) -> String {
+        self.get_device_name()
+    }
+
+    fn preferred_batch_size(&self) -> u64 {
+        1_000_000
+    }
+}
+
 impl Drop for CudaVanityGenerator {
     fn drop(&mut self) {
-        unsafe {
-            cuda_cleanup();
+        if let Ok(driver) = driver() {
+            unsafe {
+                (driver.cuCtxDestroy_v2)(self.context);
+            }
+            let _ = self.module;
         }
     }
 }
@@ -162,7 +1156,8 @@ mod tests {
     fn test_cuda_search_small_batch() {
         if let Ok(gpu) = CudaVanityGenerator::new() {
             // Test with a very small batch to see if it works
-            match gpu.search_vanity_id("a", 0, 1000) {
+            let criteria = MatchCriteria::prefix("a").unwrap();
+            match gpu.search_vanity_id(&criteria, 0, 1000) {
                 Ok(result) => {
                     if let Some((counter, key_data)) = result {
                         println!("Found match at counter {}: {:?}", counter, key_data);
@@ -176,4 +1171,78 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_cuda_search_streamed_multiple_slots() {
+        if let Ok(gpu) = CudaVanityGenerator::new() {
+            // Unmatchable criteria plus a `found` flag flipped from inside
+            // `progress` bounds the otherwise-infinite streamed loop: once
+            // enough batches have completed to prove more than one stream
+            // actually overlapped, stop it instead of grinding forever.
+            let criteria = MatchCriteria::unmatchable();
+            let next_batch = AtomicU64::new(0);
+            let found = AtomicBool::new(false);
+            let mut progress_calls = 0u32;
+
+            let result = gpu.search_vanity_id_streamed(
+                &criteria,
+                &next_batch,
+                1000,
+                3,
+                &found,
+                |_counters_tried, _keys_per_sec| {
+                    progress_calls += 1;
+                    if progress_calls >= 6 {
+                        found.store(true, Ordering::Relaxed);
+                    }
+                },
+            );
+
+            assert!(result.is_ok());
+            assert!(
+                progress_calls >= 3,
+                "expected multiple streamed batches across 3 in-flight streams, got {}",
+                progress_calls
+            );
+        }
+    }
+
+    #[test]
+    fn test_cuda_new_with_config_non_default() {
+        let config = CudaConfig {
+            schedule: ScheduleMode::Spin,
+            zero_copy: true,
+        };
+        if let Ok(gpu) = CudaVanityGenerator::new_with_config(0, config) {
+            // zero_copy is only honored when the device reports
+            // CU_DEVICE_ATTRIBUTE_CAN_MAP_HOST_MEMORY, so just check that a
+            // search against the differently-configured generator still
+            // works end to end.
+            let criteria = MatchCriteria::prefix("a").unwrap();
+            assert!(gpu.search_vanity_id(&criteria, 0, 1000).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_cuda_new_with_policy_index_matches_new_on_device() {
+        if let Ok(gpu) = CudaVanityGenerator::new_with_policy(DevicePolicy::Index(0)) {
+            assert_eq!(gpu.device_id(), 0);
+        }
+    }
+
+    #[test]
+    fn test_cuda_new_with_policy_highest_compute() {
+        if device_count() == 0 {
+            return;
+        }
+        match CudaVanityGenerator::new_with_policy(DevicePolicy::HighestCompute) {
+            Ok(gpu) => {
+                let criteria = MatchCriteria::prefix("a").unwrap();
+                assert!(gpu.search_vanity_id(&criteria, 0, 1000).is_ok());
+            }
+            // All visible devices may be below DEFAULT_MIN_FREE_MEMORY_BYTES
+            // on a busy shared machine; that's a valid outcome, not a bug.
+            Err(_) => {}
+        }
+    }
 }
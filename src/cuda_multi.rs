@@ -0,0 +1,157 @@
+use crate::cuda_gpu::{self, CudaConfig, CudaVanityGenerator, DevicePolicy};
+use crate::match_criteria::MatchCriteria;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Fans a single vanity-id search out across every usable CUDA device,
+/// partitioning the 64-bit counter space so devices never search the same
+/// range twice.
+pub struct MultiGpuVanityGenerator {
+    devices: Vec<CudaVanityGenerator>,
+}
+
+impl MultiGpuVanityGenerator {
+    /// Binds one `CudaVanityGenerator` per visible device, each configured
+    /// identically with `config`, unless `policy` is given — in that case a
+    /// single device is chosen per `CudaVanityGenerator::new_with_policy_and_config`
+    /// instead of fanning out to every device. Fails only if no device could
+    /// be initialized at all.
+    pub fn new(
+        config: CudaConfig,
+        policy: Option<DevicePolicy>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        if let Some(policy) = policy {
+            let gen = CudaVanityGenerator::new_with_policy_and_config(policy, config)?;
+            return Ok(MultiGpuVanityGenerator {
+                devices: vec![gen],
+            });
+        }
+
+        let count = cuda_gpu::device_count();
+        if count == 0 {
+            return Err("No CUDA devices found.".into());
+        }
+
+        let mut devices = Vec::new();
+        for device_id in 0..count as i32 {
+            match CudaVanityGenerator::new_with_config(device_id, config) {
+                Ok(gen) => devices.push(gen),
+                Err(e) => eprintln!("Skipping CUDA device {}: {}", device_id, e),
+            }
+        }
+
+        if devices.is_empty() {
+            return Err("Failed to initialize any CUDA device.".into());
+        }
+
+        Ok(MultiGpuVanityGenerator { devices })
+    }
+
+    pub fn device_count(&self) -> usize {
+        self.devices.len()
+    }
+
+    pub fn device_names(&self) -> Vec<String> {
+        self.devices.iter().map(|d| d.get_device_name()).collect()
+    }
+
+    /// Spawns one worker thread per device against the caller-supplied
+    /// `found`/`result` atomics. Every device claims its next range from the
+    /// shared `next_batch` dispenser with `fetch_add(batch_size)`, so devices
+    /// never search the same counters twice and a faster device naturally
+    /// claims more batches instead of idling on a fixed per-device slice.
+    /// Taking `found`/`result`/`next_batch` from the caller (rather than
+    /// owning them) is what lets a hybrid search layer CPU threads on top
+    /// using the same stop signal and dispenser, instead of the GPU pool and
+    /// the CPU pool racing over disjoint, statically-sized ranges.
+    ///
+    /// Returns the join handles together with a shared per-device attempt
+    /// counter, indexed the same as `device_names()`, for progress reporting.
+    pub fn spawn(
+        self,
+        criteria: &MatchCriteria,
+        next_batch: Arc<AtomicU64>,
+        batch_size: u64,
+        found: Arc<AtomicBool>,
+        result: Arc<Mutex<Option<(u64, [u8; 32])>>>,
+    ) -> (Vec<JoinHandle<()>>, Arc<Mutex<Vec<u64>>>) {
+        let device_attempts = Arc::new(Mutex::new(vec![0u64; self.devices.len()]));
+
+        let handles: Vec<_> = self
+            .devices
+            .into_iter()
+            .enumerate()
+            .map(|(idx, gpu)| {
+                let found = Arc::clone(&found);
+                let result = Arc::clone(&result);
+                let device_attempts = Arc::clone(&device_attempts);
+                let next_batch = Arc::clone(&next_batch);
+                let criteria = criteria.clone();
+
+                thread::spawn(move || {
+                    // Keep CUDA_STREAMS batches in flight on this device so
+                    // the next batch launches while the previous one is
+                    // still executing, instead of blocking per batch (see
+                    // `CudaVanityGenerator::search_vanity_id_streamed`).
+                    const CUDA_STREAMS: usize = 2;
+
+                    let streamed_result = gpu.search_vanity_id_streamed(
+                        &criteria,
+                        &next_batch,
+                        batch_size,
+                        CUDA_STREAMS,
+                        &found,
+                        |counters_tried, _keys_per_sec| {
+                            device_attempts.lock().unwrap()[idx] = counters_tried;
+                        },
+                    );
+
+                    match streamed_result {
+                        Ok(Some((counter, key_data))) => {
+                            if !found.swap(true, Ordering::Relaxed) {
+                                *result.lock().unwrap() = Some((counter, key_data));
+                            }
+                        }
+                        Ok(None) => {
+                            // Another device (or CPU thread, in the hybrid
+                            // search) already found the match.
+                        }
+                        Err(e) => {
+                            eprintln!("CUDA device {} error: {}", gpu.device_id(), e);
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        (handles, device_attempts)
+    }
+
+    /// Searches for `criteria` starting at `start_counter` and blocks until a
+    /// match is found, for callers that don't need live progress reporting.
+    pub fn search_vanity_id(
+        self,
+        criteria: &MatchCriteria,
+        start_counter: u64,
+        batch_size: u64,
+    ) -> Result<Option<(u64, [u8; 32])>, Box<dyn std::error::Error>> {
+        let found = Arc::new(AtomicBool::new(false));
+        let result: Arc<Mutex<Option<(u64, [u8; 32])>>> = Arc::new(Mutex::new(None));
+        let next_batch = Arc::new(AtomicU64::new(start_counter));
+
+        let (handles, _device_attempts) = self.spawn(
+            criteria,
+            next_batch,
+            batch_size,
+            Arc::clone(&found),
+            Arc::clone(&result),
+        );
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        Ok(result.lock().unwrap().take())
+    }
+}
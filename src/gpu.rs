@@ -1,10 +1,20 @@
+use crate::backend::VanityBackend;
+use crate::match_criteria::MatchCriteria;
 use metal::*;
 use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 pub struct GpuVanityGenerator {
     device: Device,
     command_queue: CommandQueue,
     compute_pipeline: ComputePipelineState,
+    /// The device's `timestamp` counter set, if it exposes one. Used to
+    /// attach an `MTLCounterSampleBuffer` to compute passes so batch timing
+    /// reflects real GPU compute time instead of CPU wall-clock around
+    /// `wait_until_completed` (which the metal-rs community has measured
+    /// running up to ~12x longer than the kernel itself). `None` on devices
+    /// without GPU counter support; callers fall back to CPU-side timing.
+    timestamp_counter_set: Option<CounterSet>,
 }
 
 impl GpuVanityGenerator {
@@ -34,21 +44,32 @@ impl GpuVanityGenerator {
             .new_compute_pipeline_state_with_function(&function)
             .map_err(|e| format!("Failed to create compute pipeline: {}", e))?;
 
+        let timestamp_counter_set = device
+            .counter_sets()
+            .iter()
+            .find(|counter_set| counter_set.name() == "timestamp")
+            .cloned();
+        if timestamp_counter_set.is_none() {
+            println!(
+                "This GPU does not expose a timestamp counter set; falling back to CPU-observed timing only."
+            );
+        }
+
         Ok(GpuVanityGenerator {
             device,
             command_queue,
             compute_pipeline,
+            timestamp_counter_set,
         })
     }
 
     pub fn search_vanity_id(
         &self,
-        prefix: &str,
+        criteria: &MatchCriteria,
         start_counter: u64,
         batch_size: u64,
     ) -> Result<Option<(u64, [u8; 32])>, Box<dyn std::error::Error>> {
-        let prefix_bytes = prefix.as_bytes();
-        let prefix_len = prefix_bytes.len() as u32;
+        let spec = criteria.gpu_spec();
 
         // Create buffers
         let results_size = mem::size_of::<u32>() * 11; // [found_flag, counter_low, counter_high, key_data_as_8_u32s]
@@ -56,15 +77,9 @@ impl GpuVanityGenerator {
             .device
             .new_buffer(results_size as u64, MTLResourceOptions::StorageModeShared);
 
-        let prefix_buffer = self.device.new_buffer_with_data(
-            prefix_bytes.as_ptr() as *const _,
-            prefix_bytes.len() as u64,
-            MTLResourceOptions::StorageModeShared,
-        );
-
-        let prefix_len_buffer = self.device.new_buffer_with_data(
-            &prefix_len as *const u32 as *const _,
-            mem::size_of::<u32>() as u64,
+        let spec_buffer = self.device.new_buffer_with_data(
+            spec.as_ptr() as *const _,
+            (spec.len() * mem::size_of::<u32>()) as u64,
             MTLResourceOptions::StorageModeShared,
         );
 
@@ -89,9 +104,8 @@ impl GpuVanityGenerator {
         // Set compute pipeline and buffers
         encoder.set_compute_pipeline_state(&self.compute_pipeline);
         encoder.set_buffer(0, Some(&results_buffer), 0);
-        encoder.set_buffer(1, Some(&prefix_buffer), 0);
-        encoder.set_buffer(2, Some(&prefix_len_buffer), 0);
-        encoder.set_buffer(3, Some(&start_counter_buffer), 0);
+        encoder.set_buffer(1, Some(&spec_buffer), 0);
+        encoder.set_buffer(2, Some(&start_counter_buffer), 0);
 
         // Calculate thread group sizes
         let max_threads_per_group = self.compute_pipeline.max_total_threads_per_threadgroup();
@@ -135,6 +149,124 @@ impl GpuVanityGenerator {
         Ok(None)
     }
 
+    /// Like `search_vanity_id`, but also measures the GPU's own compute time
+    /// for the batch via an `MTLCounterSampleBuffer` timestamp pair attached
+    /// to the compute pass, instead of relying solely on CPU wall-clock
+    /// around `wait_until_completed`. Returns `(result, gpu_elapsed_secs)`,
+    /// where `gpu_elapsed_secs` is `None` on devices without a `timestamp`
+    /// counter set — callers should fall back to their own CPU-side timing
+    /// in that case.
+    pub fn search_vanity_id_timed(
+        &self,
+        criteria: &MatchCriteria,
+        start_counter: u64,
+        batch_size: u64,
+    ) -> Result<(Option<(u64, [u8; 32])>, Option<f64>), Box<dyn std::error::Error>> {
+        let spec = criteria.gpu_spec();
+
+        let results_size = mem::size_of::<u32>() * 11;
+        let results_buffer = self
+            .device
+            .new_buffer(results_size as u64, MTLResourceOptions::StorageModeShared);
+
+        let spec_buffer = self.device.new_buffer_with_data(
+            spec.as_ptr() as *const _,
+            (spec.len() * mem::size_of::<u32>()) as u64,
+            MTLResourceOptions::StorageModeShared,
+        );
+        let start_counter_buffer = self.device.new_buffer_with_data(
+            &start_counter as *const u64 as *const _,
+            mem::size_of::<u64>() as u64,
+            MTLResourceOptions::StorageModeShared,
+        );
+
+        unsafe {
+            let results_ptr = results_buffer.contents() as *mut u32;
+            for i in 0..11 {
+                *results_ptr.add(i) = 0;
+            }
+        }
+
+        let sample_buffer = self.timestamp_counter_set.as_ref().and_then(|counter_set| {
+            let descriptor = CounterSampleBufferDescriptor::new();
+            descriptor.set_counter_set(counter_set);
+            descriptor.set_storage_mode(MTLStorageMode::Shared);
+            descriptor.set_sample_count(2);
+            self.device
+                .new_counter_sample_buffer_with_descriptor(&descriptor)
+                .ok()
+        });
+
+        let command_buffer = self.command_queue.new_command_buffer();
+
+        let encoder = match sample_buffer.as_ref() {
+            Some(sample_buffer) => {
+                let pass_descriptor = ComputePassDescriptor::new();
+                let attachment = pass_descriptor
+                    .sample_buffer_attachments()
+                    .object_at(0)
+                    .unwrap();
+                attachment.set_sample_buffer(sample_buffer);
+                attachment.set_start_of_encoder_sample_index(0);
+                attachment.set_end_of_encoder_sample_index(1);
+                command_buffer.compute_command_encoder_with_descriptor(&pass_descriptor)
+            }
+            None => command_buffer.new_compute_command_encoder(),
+        };
+
+        encoder.set_compute_pipeline_state(&self.compute_pipeline);
+        encoder.set_buffer(0, Some(&results_buffer), 0);
+        encoder.set_buffer(1, Some(&spec_buffer), 0);
+        encoder.set_buffer(2, Some(&start_counter_buffer), 0);
+
+        let max_threads_per_group = self.compute_pipeline.max_total_threads_per_threadgroup();
+        let threads_per_group = std::cmp::min(max_threads_per_group, 256);
+        let thread_groups = (batch_size + threads_per_group as u64 - 1) / threads_per_group as u64;
+        encoder.dispatch_thread_groups(
+            MTLSize::new(thread_groups, 1, 1),
+            MTLSize::new(threads_per_group, 1, 1),
+        );
+
+        encoder.end_encoding();
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+
+        let gpu_elapsed = sample_buffer.and_then(|sample_buffer| {
+            let samples = sample_buffer.resolve_counter_range(0..2)?;
+            if samples.len() < 16 {
+                return None;
+            }
+            let start_ns = u64::from_ne_bytes(samples[0..8].try_into().ok()?);
+            let end_ns = u64::from_ne_bytes(samples[8..16].try_into().ok()?);
+            Some(end_ns.saturating_sub(start_ns) as f64 / 1_000_000_000.0)
+        });
+
+        let found = unsafe {
+            let results_ptr = results_buffer.contents() as *const u32;
+            let found_flag = *results_ptr;
+
+            if found_flag != 0 {
+                let counter_low = *results_ptr.add(1) as u64;
+                let counter_high = *results_ptr.add(2) as u64;
+                let counter = counter_low | (counter_high << 32);
+
+                let mut key_data = [0u8; 32];
+                for i in 0..8 {
+                    let chunk = *results_ptr.add(3 + i);
+                    for j in 0..4 {
+                        key_data[i * 4 + j] = ((chunk >> (j * 8)) & 0xFF) as u8;
+                    }
+                }
+
+                Some((counter, key_data))
+            } else {
+                None
+            }
+        };
+
+        Ok((found, gpu_elapsed))
+    }
+
     pub fn get_max_threads_per_group(&self) -> usize {
         self.compute_pipeline.max_total_threads_per_threadgroup() as usize
     }
@@ -142,6 +274,160 @@ impl GpuVanityGenerator {
     pub fn get_device_name(&self) -> String {
         self.device.name().to_string()
     }
+
+    /// Pipelined variant of `search_vanity_id`: keeps `num_command_buffers`
+    /// batches in flight at once instead of calling
+    /// `wait_until_completed()` after every dispatch, so the GPU stays busy
+    /// while the host enqueues the next batch. `progress` is called after
+    /// each batch completes with the running total of counters tried and the
+    /// CPU-observed keys/sec for that batch.
+    ///
+    /// Batch starts are claimed from `next_batch` with `fetch_add`, the same
+    /// shared work-dispenser the CPU threads in the hybrid search use, so
+    /// this call can run alongside them against one counter space. `found`
+    /// is checked between batches so this returns promptly once a CPU thread
+    /// has already won the race, instead of dispatching more batches no one
+    /// needs.
+    pub fn search_vanity_id_streamed(
+        &self,
+        criteria: &MatchCriteria,
+        next_batch: &AtomicU64,
+        batch_size: u64,
+        num_command_buffers: usize,
+        found: &AtomicBool,
+        mut progress: impl FnMut(u64, f64),
+    ) -> Result<Option<(u64, [u8; 32])>, Box<dyn std::error::Error>> {
+        let num_command_buffers = num_command_buffers.max(1);
+        let spec = criteria.gpu_spec();
+
+        struct InFlight {
+            command_buffer: CommandBuffer,
+            results_buffer: Buffer,
+            batch_start: u64,
+            dispatched_at: std::time::Instant,
+        }
+
+        let dispatch = |batch_start: u64| -> InFlight {
+            let results_size = mem::size_of::<u32>() * 11;
+            let results_buffer = self
+                .device
+                .new_buffer(results_size as u64, MTLResourceOptions::StorageModeShared);
+            unsafe {
+                let ptr = results_buffer.contents() as *mut u32;
+                for i in 0..11 {
+                    *ptr.add(i) = 0;
+                }
+            }
+
+            let spec_buffer = self.device.new_buffer_with_data(
+                spec.as_ptr() as *const _,
+                (spec.len() * mem::size_of::<u32>()) as u64,
+                MTLResourceOptions::StorageModeShared,
+            );
+            let start_counter_buffer = self.device.new_buffer_with_data(
+                &batch_start as *const u64 as *const _,
+                mem::size_of::<u64>() as u64,
+                MTLResourceOptions::StorageModeShared,
+            );
+
+            let command_buffer = self.command_queue.new_command_buffer().to_owned();
+            let encoder = command_buffer.new_compute_command_encoder();
+            encoder.set_compute_pipeline_state(&self.compute_pipeline);
+            encoder.set_buffer(0, Some(&results_buffer), 0);
+            encoder.set_buffer(1, Some(&spec_buffer), 0);
+            encoder.set_buffer(2, Some(&start_counter_buffer), 0);
+
+            let max_threads_per_group = self.compute_pipeline.max_total_threads_per_threadgroup();
+            let threads_per_group = std::cmp::min(max_threads_per_group, 256);
+            let thread_groups =
+                (batch_size + threads_per_group as u64 - 1) / threads_per_group as u64;
+            encoder.dispatch_thread_groups(
+                MTLSize::new(thread_groups, 1, 1),
+                MTLSize::new(threads_per_group, 1, 1),
+            );
+            encoder.end_encoding();
+            command_buffer.commit();
+
+            InFlight {
+                command_buffer,
+                results_buffer,
+                batch_start,
+                dispatched_at: std::time::Instant::now(),
+            }
+        };
+
+        let mut in_flight: Vec<InFlight> = (0..num_command_buffers)
+            .map(|_| dispatch(next_batch.fetch_add(batch_size, Ordering::Relaxed)))
+            .collect();
+
+        let mut counters_tried = 0u64;
+        loop {
+            if found.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+
+            for i in 0..in_flight.len() {
+                if in_flight[i].command_buffer.status() != MTLCommandBufferStatus::Completed {
+                    continue;
+                }
+
+                let elapsed = in_flight[i].dispatched_at.elapsed().as_secs_f64();
+                let found_flag;
+                let mut counter = 0u64;
+                let mut key_data = [0u8; 32];
+                unsafe {
+                    let results_ptr = in_flight[i].results_buffer.contents() as *const u32;
+                    found_flag = *results_ptr;
+                    if found_flag != 0 {
+                        let counter_low = *results_ptr.add(1) as u64;
+                        let counter_high = *results_ptr.add(2) as u64;
+                        counter = counter_low | (counter_high << 32);
+                        for k in 0..8 {
+                            let chunk = *results_ptr.add(3 + k);
+                            for j in 0..4 {
+                                key_data[k * 4 + j] = ((chunk >> (j * 8)) & 0xFF) as u8;
+                            }
+                        }
+                    }
+                }
+
+                counters_tried += batch_size;
+                let keys_per_sec = if elapsed > 0.0 {
+                    batch_size as f64 / elapsed
+                } else {
+                    0.0
+                };
+                progress(counters_tried, keys_per_sec);
+
+                if found_flag != 0 {
+                    return Ok(Some((counter, key_data)));
+                }
+
+                in_flight[i] = dispatch(next_batch.fetch_add(batch_size, Ordering::Relaxed));
+            }
+
+            std::thread::yield_now();
+        }
+    }
+}
+
+impl VanityBackend for GpuVanityGenerator {
+    fn search(
+        &self,
+        criteria: &MatchCriteria,
+        start_counter: u64,
+        batch_size: u64,
+    ) -> Result<Option<(u64, [u8; 32])>, Box<dyn std::error::Error>> {
+        self.search_vanity_id(criteria, start_counter, batch_size)
+    }
+
+    fn device_name(&self) -> String {
+        self.get_device_name()
+    }
+
+    fn preferred_batch_size(&self) -> u64 {
+        1_000_000
+    }
 }
 
 #[cfg(test)]
@@ -166,7 +452,8 @@ mod tests {
     fn test_gpu_search_small_batch() {
         if let Ok(gpu) = GpuVanityGenerator::new() {
             // Test with a very small batch to see if it works
-            match gpu.search_vanity_id("a", 0, 1000) {
+            let criteria = MatchCriteria::prefix("a").unwrap();
+            match gpu.search_vanity_id(&criteria, 0, 1000) {
                 Ok(result) => {
                     if let Some((counter, key_data)) = result {
                         println!("Found match at counter {}: {:?}", counter, key_data);
@@ -180,4 +467,39 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_gpu_search_streamed_multiple_slots() {
+        if let Ok(gpu) = GpuVanityGenerator::new() {
+            // Unmatchable criteria plus a `found` flag flipped from inside
+            // `progress` bounds the otherwise-infinite streamed loop: once
+            // enough batches have completed to prove more than one slot
+            // actually overlapped, stop it instead of grinding forever.
+            let criteria = MatchCriteria::unmatchable();
+            let next_batch = AtomicU64::new(0);
+            let found = AtomicBool::new(false);
+            let mut progress_calls = 0u32;
+
+            let result = gpu.search_vanity_id_streamed(
+                &criteria,
+                &next_batch,
+                1000,
+                3,
+                &found,
+                |_counters_tried, _keys_per_sec| {
+                    progress_calls += 1;
+                    if progress_calls >= 6 {
+                        found.store(true, Ordering::Relaxed);
+                    }
+                },
+            );
+
+            assert!(result.is_ok());
+            assert!(
+                progress_calls >= 3,
+                "expected multiple streamed batches across 3 in-flight slots, got {}",
+                progress_calls
+            );
+        }
+    }
 }
@@ -3,7 +3,7 @@ use base64::Engine;
 use clap::Parser;
 use num_format::{Locale, ToFormattedString};
 use sha2::{Digest, Sha256};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Instant;
@@ -15,21 +15,163 @@ use gpu::GpuVanityGenerator;
 
 #[cfg(feature = "cuda")]
 mod cuda_gpu;
+
+#[cfg(feature = "cuda")]
+mod cuda_multi;
+#[cfg(feature = "cuda")]
+use cuda_multi::MultiGpuVanityGenerator;
+
+#[cfg(feature = "opencl")]
+mod opencl_gpu;
+#[cfg(feature = "opencl")]
+use opencl_gpu::OpenClVanityGenerator;
+
+mod backend;
+use backend::VanityBackend;
+
+mod benchmark;
+
+mod checkpoint;
+use checkpoint::Checkpoint;
+
+mod match_criteria;
+use match_criteria::MatchCriteria;
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+#[clap(rename_all = "lower")]
+enum CudaSchedule {
+    /// Let the driver pick (spins if there's only one context, otherwise
+    /// yields).
+    Auto,
+    /// Busy-wait for GPU work to finish. Lowest latency, but pins a host
+    /// core at 100%.
+    Spin,
+    /// Yield the host thread while waiting.
+    Yield,
+    /// Block the host thread on a synchronization primitive while waiting.
+    BlockingSync,
+}
+
+#[cfg(feature = "cuda")]
+impl From<CudaSchedule> for cuda_gpu::ScheduleMode {
+    fn from(schedule: CudaSchedule) -> Self {
+        match schedule {
+            CudaSchedule::Auto => cuda_gpu::ScheduleMode::Auto,
+            CudaSchedule::Spin => cuda_gpu::ScheduleMode::Spin,
+            CudaSchedule::Yield => cuda_gpu::ScheduleMode::Yield,
+            CudaSchedule::BlockingSync => cuda_gpu::ScheduleMode::BlockingSync,
+        }
+    }
+}
+
+#[cfg(feature = "cuda")]
+fn cuda_config(cli: &Cli) -> cuda_gpu::CudaConfig {
+    cuda_gpu::CudaConfig {
+        schedule: cli.cuda_schedule.into(),
+        zero_copy: cli.cuda_zero_copy,
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+enum CudaDevicePolicy {
+    /// Pick the device with the highest compute capability, breaking ties
+    /// by free memory.
+    HighestCompute,
+    /// Pick the device reporting the most free global memory.
+    MostFreeMemory,
+}
+
+/// Resolves `--cuda-device`/`--cuda-device-policy` into a `DevicePolicy`, or
+/// `None` if neither was passed (fan out to every visible device).
 #[cfg(feature = "cuda")]
-use cuda_gpu::CudaVanityGenerator;
+fn cuda_device_policy(cli: &Cli) -> Option<cuda_gpu::DevicePolicy> {
+    if let Some(device) = cli.cuda_device {
+        return Some(cuda_gpu::DevicePolicy::Index(device));
+    }
+    match cli.cuda_device_policy? {
+        CudaDevicePolicy::HighestCompute => Some(cuda_gpu::DevicePolicy::HighestCompute),
+        CudaDevicePolicy::MostFreeMemory => Some(cuda_gpu::DevicePolicy::MostFreeMemory),
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+#[clap(rename_all = "lower")]
+enum Backend {
+    /// Metal on macOS, not available elsewhere.
+    Metal,
+    /// NVIDIA CUDA, via the driver API.
+    Cuda,
+    /// OpenCL (AMD/Intel GPUs, or any other OpenCL-capable device).
+    OpenCl,
+    /// Pick Metal on macOS, CUDA elsewhere (matches the old --gpu behavior).
+    Auto,
+}
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    #[arg(short, long, default_value = "ok")]
+    #[arg(short, long, default_value = "ok", help = "Require this literal prefix (in the 'a'..'p' alphabet); ignored if --suffix/--contains/--pattern/--leading is given")]
     prefix: String,
 
+    #[arg(long, help = "Require this literal suffix instead of a prefix")]
+    suffix: Option<String>,
+
+    #[arg(
+        long,
+        help = "Require this literal substring anywhere in the extension ID"
+    )]
+    contains: Option<String>,
+
+    #[arg(
+        long,
+        help = "Match a fixed-length template anchored at the start: '.' matches any character, '[...]' matches a character class (e.g. \"a[a-f].\")"
+    )]
+    pattern: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Require N leading 'a' characters (the zero-nibble), like a leading-zero-run difficulty target"
+    )]
+    leading: Option<usize>,
+
     #[arg(short, long, default_value_t = num_cpus::get())]
     cores: usize,
 
     #[arg(long)]
     single_thread: bool,
 
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "shard_count",
+        help = "This machine's shard index (0-based); pair with --shard-count to split a search across machines without overlap"
+    )]
+    shard_index: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "M",
+        requires = "shard_index",
+        help = "Total number of shards the counter space is split into"
+    )]
+    shard_count: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Periodically persist each thread's search progress to PATH so the search can be restarted with --resume"
+    )]
+    checkpoint: Option<String>,
+
+    #[arg(
+        long,
+        requires = "checkpoint",
+        help = "Resume from the progress saved in --checkpoint instead of starting from the beginning of this shard"
+    )]
+    resume: bool,
+
     #[arg(
         long,
         help = "Use GPU acceleration (Metal on macOS, CUDA on Windows/Linux)"
@@ -42,23 +184,165 @@ struct Cli {
     )]
     hybrid: bool,
 
+    #[arg(
+        long,
+        value_enum,
+        default_value = "auto",
+        help = "Which GPU backend to use with --gpu/--hybrid"
+    )]
+    backend: Backend,
+
     #[arg(
         long,
         default_value_t = 1_000_000,
         help = "GPU batch size for each compute dispatch"
     )]
     gpu_batch_size: u64,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Benchmark every available backend for SECONDS against an unmatchable prefix and print a comparison table"
+    )]
+    benchmark: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "benchmark",
+        help = "Also write --benchmark results to PATH as JSON"
+    )]
+    benchmark_json: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "auto",
+        help = "CUDA context scheduling mode used by --backend cuda/--hybrid (passed to cuCtxCreate_v2)"
+    )]
+    cuda_schedule: CudaSchedule,
+
+    #[arg(
+        long,
+        help = "Allocate CUDA results buffers as pinned, zero-copy host memory on devices that support it"
+    )]
+    cuda_zero_copy: bool,
+
+    #[arg(
+        long,
+        value_name = "INDEX",
+        conflicts_with = "cuda_device_policy",
+        help = "Bind to a specific CUDA device index instead of using every visible device"
+    )]
+    cuda_device: Option<i32>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Pick one CUDA device automatically (by compute capability or free memory) instead of using every visible device"
+    )]
+    cuda_device_policy: Option<CudaDevicePolicy>,
 }
 
-const MAPPING: [char; 16] = [
+pub(crate) const MAPPING: [char; 16] = [
     'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p',
 ];
 
+/// Picks whichever match flag the user actually passed, preferring the more
+/// specific ones over the always-present `--prefix` default so `--pattern`
+/// etc. don't need `--prefix ""` to take effect.
+fn compile_match_criteria(cli: &Cli) -> Result<MatchCriteria, String> {
+    if let Some(pattern) = &cli.pattern {
+        MatchCriteria::pattern(pattern)
+    } else if let Some(contains) = &cli.contains {
+        MatchCriteria::contains(contains)
+    } else if let Some(suffix) = &cli.suffix {
+        MatchCriteria::suffix(suffix)
+    } else if let Some(count) = cli.leading {
+        Ok(MatchCriteria::leading(count))
+    } else {
+        MatchCriteria::prefix(&cli.prefix)
+    }
+}
+
+/// Prints what's being searched for plus how long it should take, so users
+/// know up front whether a 10-character pattern is actually feasible.
+fn print_match_banner(criteria: &MatchCriteria) {
+    println!("Searching for extension ID matching {}", criteria.description());
+    println!(
+        "Expected attempts: ~{}",
+        criteria.expected_attempts().to_formatted_string(&Locale::en)
+    );
+}
+
+/// Formats a seconds count as a coarse human-readable ETA, e.g. "3h 12m".
+/// Used instead of a fixed-precision float since the estimate itself is
+/// rarely meaningful to more than two significant parts.
+fn format_eta(seconds: f64) -> String {
+    if !seconds.is_finite() || seconds < 0.0 {
+        return "unknown".to_string();
+    }
+    let total_secs = seconds.round() as u64;
+    let days = total_secs / 86_400;
+    let hours = (total_secs % 86_400) / 3600;
+    let mins = (total_secs % 3600) / 60;
+    let secs = total_secs % 60;
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m {}s", mins, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Prints a probability-aware companion line under the raw attempts/rate
+/// progress line: the expected attempts for this criterion, a median-time
+/// ETA, and the odds a match has already turned up by now. Most useful on
+/// long prefixes, where raw attempt counts and keys/sec don't tell a user
+/// whether a multi-hour search is on track or already overdue. No-op until
+/// `rate` is known.
+fn print_probability_progress(criteria: &MatchCriteria, total_attempts: u64, rate: f64) {
+    if rate <= 0.0 {
+        return;
+    }
+    println!(
+        "  Expected attempts: ~{}, median ETA: {}, P(found by now): {:.1}%",
+        criteria.expected_attempts().to_formatted_string(&Locale::en),
+        criteria
+            .median_eta_secs(rate)
+            .map(format_eta)
+            .unwrap_or_else(|| "unknown".to_string()),
+        criteria.cumulative_probability(total_attempts) * 100.0
+    );
+}
+
 fn main() {
     let cli = Cli::parse();
 
+    if let Some(seconds) = cli.benchmark {
+        run_benchmark(
+            seconds,
+            cli.gpu_batch_size,
+            cli.benchmark_json.as_deref(),
+            cli.cores,
+            cli.single_thread,
+        );
+        return;
+    }
+
+    let criteria = match compile_match_criteria(&cli) {
+        Ok(criteria) => criteria,
+        Err(e) => {
+            eprintln!("Invalid match criteria: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     if cli.hybrid {
-        println!("Searching for extension ID with prefix: {}", cli.prefix);
+        print_match_banner(&criteria);
         println!("Using hybrid mode: GPU + CPU simultaneously");
         // Use fewer CPU threads in hybrid mode to avoid resource contention with GPU
         let cpu_threads = if cli.single_thread {
@@ -66,36 +350,138 @@ fn main() {
         } else {
             std::cmp::max(1, cli.cores / 4)
         };
-        #[cfg(target_os = "macos")]
-        run_hybrid_vanity_id_generator(&cli.prefix, cpu_threads, cli.gpu_batch_size);
-        #[cfg(not(target_os = "macos"))]
-        run_cuda_hybrid_vanity_id_generator(&cli.prefix, cpu_threads, cli.gpu_batch_size);
+        match cli.backend {
+            Backend::Metal => {
+                #[cfg(target_os = "macos")]
+                run_hybrid_vanity_id_generator(&criteria, cpu_threads, cli.gpu_batch_size);
+                #[cfg(not(target_os = "macos"))]
+                backend_unavailable("Metal", "this build was not compiled on macOS");
+            }
+            Backend::Cuda => {
+                #[cfg(feature = "cuda")]
+                run_cuda_hybrid_vanity_id_generator(
+                    &criteria,
+                    cpu_threads,
+                    cli.gpu_batch_size,
+                    cuda_config(&cli),
+                    cuda_device_policy(&cli),
+                );
+                #[cfg(not(feature = "cuda"))]
+                backend_unavailable("CUDA", "this build has the cuda feature disabled");
+            }
+            Backend::OpenCl => {
+                #[cfg(feature = "opencl")]
+                run_opencl_hybrid_vanity_id_generator(&criteria, cpu_threads, cli.gpu_batch_size);
+                #[cfg(not(feature = "opencl"))]
+                backend_unavailable("OpenCL", "this build has the opencl feature disabled");
+            }
+            Backend::Auto => {
+                #[cfg(target_os = "macos")]
+                run_hybrid_vanity_id_generator(&criteria, cpu_threads, cli.gpu_batch_size);
+                #[cfg(not(target_os = "macos"))]
+                run_cuda_hybrid_vanity_id_generator(
+                    &criteria,
+                    cpu_threads,
+                    cli.gpu_batch_size,
+                    cuda_config(&cli),
+                    cuda_device_policy(&cli),
+                );
+            }
+        }
         return;
     }
 
     if cli.gpu {
-        println!("Searching for extension ID with prefix: {}", cli.prefix);
-        #[cfg(target_os = "macos")]
-        {
-            println!("Using GPU acceleration (Metal - Apple Silicon)");
-            run_gpu_vanity_id_generator(&cli.prefix, cli.gpu_batch_size);
-        }
-        #[cfg(not(target_os = "macos"))]
-        {
-            println!("Using GPU acceleration (CUDA - NVIDIA)");
-            run_cuda_gpu_vanity_id_generator(&cli.prefix, cli.gpu_batch_size);
+        print_match_banner(&criteria);
+        match cli.backend {
+            Backend::Metal => {
+                #[cfg(target_os = "macos")]
+                {
+                    println!("Using GPU acceleration (Metal - Apple Silicon)");
+                    run_gpu_vanity_id_generator(&criteria, cli.gpu_batch_size);
+                }
+                #[cfg(not(target_os = "macos"))]
+                backend_unavailable("Metal", "this build was not compiled on macOS");
+            }
+            Backend::Cuda => {
+                #[cfg(feature = "cuda")]
+                {
+                    println!("Using GPU acceleration (CUDA - NVIDIA)");
+                    run_cuda_vanity_id_generator(
+                        &criteria,
+                        cli.gpu_batch_size,
+                        cuda_config(&cli),
+                        cuda_device_policy(&cli),
+                    );
+                }
+                #[cfg(not(feature = "cuda"))]
+                backend_unavailable("CUDA", "this build has the cuda feature disabled");
+            }
+            Backend::OpenCl => {
+                #[cfg(feature = "opencl")]
+                {
+                    println!("Using GPU acceleration (OpenCL)");
+                    run_opencl_vanity_id_generator(&criteria, cli.gpu_batch_size);
+                }
+                #[cfg(not(feature = "opencl"))]
+                backend_unavailable("OpenCL", "this build has the opencl feature disabled");
+            }
+            Backend::Auto => {
+                #[cfg(target_os = "macos")]
+                {
+                    println!("Using GPU acceleration (Metal - Apple Silicon)");
+                    run_gpu_vanity_id_generator(&criteria, cli.gpu_batch_size);
+                }
+                #[cfg(not(target_os = "macos"))]
+                {
+                    println!("Using GPU acceleration (CUDA - NVIDIA)");
+                    run_cuda_vanity_id_generator(
+                        &criteria,
+                        cli.gpu_batch_size,
+                        cuda_config(&cli),
+                        cuda_device_policy(&cli),
+                    );
+                }
+            }
         }
         return;
     }
 
-    let thread_count = if cli.single_thread { 1 } else { cli.cores };
-    println!("Searching for extension ID with prefix: {}", cli.prefix);
+    let thread_count = if cli.single_thread { 1 } else { cli.cores.max(1) };
+    let shard_index = cli.shard_index.unwrap_or(0);
+    let shard_count = cli.shard_count.unwrap_or(1).max(1);
+    if shard_index >= shard_count {
+        eprintln!(
+            "--shard-index {} is out of range for --shard-count {}",
+            shard_index, shard_count
+        );
+        std::process::exit(1);
+    }
+
+    print_match_banner(&criteria);
     println!("Using {} thread(s)", thread_count);
+    if shard_count > 1 {
+        println!("Searching shard {} of {}", shard_index, shard_count);
+    }
 
-    run_vanity_id_generator(&cli.prefix, thread_count);
+    run_vanity_id_generator(
+        &criteria,
+        thread_count,
+        shard_index,
+        shard_count,
+        cli.checkpoint.as_deref(),
+        cli.resume,
+    );
 }
 
-fn run_vanity_id_generator(desired_prefix: &str, num_threads: usize) {
+fn run_vanity_id_generator(
+    criteria: &MatchCriteria,
+    num_threads: usize,
+    shard_index: usize,
+    shard_count: usize,
+    checkpoint_path: Option<&str>,
+    resume: bool,
+) {
     let start_time = Instant::now();
     let found = Arc::new(AtomicBool::new(false));
     let result = Arc::new(Mutex::new(None));
@@ -104,30 +490,55 @@ fn run_vanity_id_generator(desired_prefix: &str, num_threads: usize) {
     // Shared progress tracking - each thread will report its attempts
     let thread_attempts = Arc::new(Mutex::new(vec![0u64; num_threads]));
 
-    // Calculate counter ranges for each thread to avoid overlap
-    // Each thread gets a large range to work with independently
-    const THREAD_RANGE_SIZE: u64 = u64::MAX / 1024; // Large range per thread
+    // Partition the counter space into `shard_count` equal stripes and only
+    // search stripe `shard_index`, so multiple machines can split the same
+    // search without overlap. Within a shard, threads divide it the same way
+    // the single-machine case always has: each gets a large range of its own.
+    let shard_size = u64::MAX / shard_count as u64;
+    let shard_base = shard_index as u64 * shard_size;
+    let per_thread_range = shard_size / num_threads as u64;
+
+    // Lowest un-searched counter per thread, checkpointed to disk so a long
+    // search for a rare prefix can be restarted instead of starting over.
+    let default_starts: Vec<u64> = (0..num_threads)
+        .map(|thread_id| shard_base + (thread_id as u64) * per_thread_range)
+        .collect();
+    let thread_starts = if resume {
+        match checkpoint_path.map(|path| Checkpoint::load(path, num_threads)) {
+            Some(Ok(checkpoint)) => checkpoint.counters,
+            Some(Err(e)) => {
+                eprintln!("Failed to resume from checkpoint: {}", e);
+                std::process::exit(1);
+            }
+            None => default_starts,
+        }
+    } else {
+        default_starts
+    };
+    let checkpoint_counters = Arc::new(Mutex::new(thread_starts.clone()));
+    const CHECKPOINT_INTERVAL: u64 = 5_000_000; // Persist progress every few million attempts
 
     // Spawn worker threads
     let handles: Vec<_> = (0..num_threads)
         .map(|thread_id| {
-            let prefix = desired_prefix.to_string();
+            let criteria = criteria.clone();
             let found = Arc::clone(&found);
             let result = Arc::clone(&result);
             let start_time = start_time.clone();
             let last_progress_time = Arc::clone(&last_progress_time);
             let thread_attempts = Arc::clone(&thread_attempts);
+            let checkpoint_counters = Arc::clone(&checkpoint_counters);
+            let checkpoint_path = checkpoint_path.map(|p| p.to_string());
+            let thread_start_counter = thread_starts[thread_id];
 
             thread::spawn(move || {
-                // Each thread calculates its own starting counter to avoid overlap
-                let thread_start_counter = (thread_id as u64) * THREAD_RANGE_SIZE;
                 let mut local_counter = thread_start_counter;
                 let mut local_attempts = 0u64;
                 const PROGRESS_REPORT_INTERVAL: u64 = 500000; // Report progress every 500k attempts in hybrid mode
 
                 while !found.load(Ordering::Relaxed) {
                     if let Some((ext_id, key_data)) =
-                        try_generate_match_optimized(&prefix, local_counter)
+                        try_generate_match_optimized(&criteria, local_counter)
                     {
                         if !found.swap(true, Ordering::Relaxed) {
                             *result.lock().unwrap() = Some((ext_id, key_data, local_attempts + 1));
@@ -163,14 +574,31 @@ fn run_vanity_id_generator(desired_prefix: &str, num_threads: usize) {
                                 if elapsed > 0.0 {
                                     let rate = total as f64 / elapsed;
                                     println!(
-                                        "Progress: {} attempts, {} keys/sec",
+                                        "Progress: {} attempts, {} keys/sec, ETA {}",
                                         total.to_formatted_string(&Locale::en),
-                                        (rate as u64).to_formatted_string(&Locale::en)
+                                        (rate as u64).to_formatted_string(&Locale::en),
+                                        criteria
+                                            .eta_secs(rate)
+                                            .map(format_eta)
+                                            .unwrap_or_else(|| "unknown".to_string())
                                     );
                                 }
+
+                                if let Some(path) = checkpoint_path.as_deref() {
+                                    let checkpoint = Checkpoint {
+                                        counters: checkpoint_counters.lock().unwrap().clone(),
+                                    };
+                                    if let Err(e) = checkpoint.save(path) {
+                                        eprintln!("Failed to write checkpoint {}: {}", path, e);
+                                    }
+                                }
                             }
                         }
                     }
+
+                    if local_attempts % CHECKPOINT_INTERVAL == 0 {
+                        checkpoint_counters.lock().unwrap()[thread_id] = local_counter;
+                    }
                 }
 
                 // Final update of this thread's attempts
@@ -219,15 +647,15 @@ fn run_vanity_id_generator(desired_prefix: &str, num_threads: usize) {
     }
 }
 
-fn try_generate_match_optimized(desired_prefix: &str, counter: u64) -> Option<(String, [u8; 32])> {
+fn try_generate_match_optimized(criteria: &MatchCriteria, counter: u64) -> Option<(String, [u8; 32])> {
     // Generate key data from counter
     let key_data = generate_key_data(counter);
 
     // Hash the key data
     let hash = Sha256::digest(&key_data);
 
-    // Optimized hash matching with early exit
-    if hash_matches_prefix_optimized(&hash, desired_prefix) {
+    // Precompiled mask/anchor check with early exit
+    if criteria.matches(&hash) {
         let extension_id = hash_to_extension_id(&hash);
         Some((extension_id, key_data))
     } else {
@@ -250,46 +678,6 @@ fn generate_key_data(counter: u64) -> [u8; 32] {
     data
 }
 
-fn hash_matches_prefix_optimized(hash: &[u8], prefix: &str) -> bool {
-    let prefix_bytes = prefix.as_bytes();
-    let prefix_len = prefix_bytes.len();
-
-    // Early exit for empty prefix
-    if prefix_len == 0 {
-        return true;
-    }
-
-    // Process pairs of characters (full bytes) first for better performance
-    let full_bytes = prefix_len / 2;
-    for byte_idx in 0..full_bytes {
-        let hash_byte = hash[byte_idx];
-        let expected_high = prefix_bytes[byte_idx * 2];
-        let expected_low = prefix_bytes[byte_idx * 2 + 1];
-
-        // Convert hash byte to characters
-        let actual_high = MAPPING[(hash_byte >> 4) as usize] as u8;
-        let actual_low = MAPPING[(hash_byte & 0x0F) as usize] as u8;
-
-        // Early exit on first mismatch
-        if actual_high != expected_high || actual_low != expected_low {
-            return false;
-        }
-    }
-
-    // Handle odd-length prefix (remaining single character)
-    if prefix_len % 2 == 1 {
-        let hash_byte = hash[full_bytes];
-        let expected_char = prefix_bytes[prefix_len - 1];
-        let actual_char = MAPPING[(hash_byte >> 4) as usize] as u8;
-
-        if actual_char != expected_char {
-            return false;
-        }
-    }
-
-    true
-}
-
 fn hash_to_extension_id(hash: &[u8]) -> String {
     hash[..16]
         .iter()
@@ -303,7 +691,7 @@ fn hash_to_extension_id(hash: &[u8]) -> String {
 
 #[cfg(target_os = "macos")]
 fn run_hybrid_vanity_id_generator(
-    desired_prefix: &str,
+    criteria: &MatchCriteria,
     num_cpu_threads: usize,
     gpu_batch_size: u64,
 ) {
@@ -334,86 +722,90 @@ fn run_hybrid_vanity_id_generator(
     let gpu_attempts = Arc::new(Mutex::new(0u64));
     let cpu_attempts = Arc::new(Mutex::new(vec![0u64; num_cpu_threads]));
 
-    // Counter range allocation:
-    // GPU gets the first half of the counter space (0 to u64::MAX/2)
-    // CPU threads get the second half (u64::MAX/2 to u64::MAX)
-    const GPU_RANGE_START: u64 = 0;
-    const CPU_RANGE_START: u64 = u64::MAX / 2;
-    const CPU_THREAD_RANGE_SIZE: u64 = (u64::MAX / 2) / 1024; // CPU threads share second half
+    // Work dispenser: GPU and CPU threads both claim their next range from
+    // this single shared counter with `fetch_add`, instead of each being
+    // carved a fixed half of the counter space up front. This means every
+    // counter is tried at most once, nothing is wasted if one side is slower
+    // than the other, and a fast GPU naturally claims more batches than a
+    // fixed split would have given it. CPU threads claim a much smaller
+    // batch than the GPU so they don't starve behind a single giant claim.
+    let next_batch = Arc::new(AtomicU64::new(0));
+    const CPU_BATCH_SIZE: u64 = 100_000;
 
     // Spawn GPU thread
     let gpu_handle = {
-        let prefix = desired_prefix.to_string();
+        let criteria = criteria.clone();
         let found = Arc::clone(&found);
         let result = Arc::clone(&result);
         let gpu_attempts = Arc::clone(&gpu_attempts);
+        let next_batch = Arc::clone(&next_batch);
         let gpu = gpu.unwrap();
 
         thread::spawn(move || {
-            let mut batch_id = 0u64;
-            let mut local_gpu_attempts = 0u64;
-
-            while !found.load(Ordering::Relaxed) {
-                // Calculate starting counter for this GPU batch
-                let batch_start_counter = GPU_RANGE_START + (batch_id * gpu_batch_size);
-
-                match gpu.search_vanity_id(&prefix, batch_start_counter, gpu_batch_size) {
-                    Ok(Some((found_counter, key_data))) => {
-                        // GPU found a match!
-                        local_gpu_attempts += found_counter - batch_start_counter + 1;
-
-                        if !found.swap(true, Ordering::Relaxed) {
-                            *result.lock().unwrap() = Some((
-                                hash_to_extension_id(&Sha256::digest(&key_data)),
-                                key_data,
-                                local_gpu_attempts,
-                                "GPU".to_string(),
-                            ));
-                        }
-                        break;
-                    }
-                    Ok(None) => {
-                        // No match in this batch, continue
-                        local_gpu_attempts += gpu_batch_size;
-                        batch_id += 1;
+            // Keep GPU_STREAMS command buffers in flight so the GPU stays
+            // busy dispatching the next batch instead of idling between
+            // `wait_until_completed()` calls (see `search_vanity_id_streamed`).
+            const GPU_STREAMS: usize = 2;
+
+            let streamed_result = gpu.search_vanity_id_streamed(
+                &criteria,
+                &next_batch,
+                gpu_batch_size,
+                GPU_STREAMS,
+                &found,
+                |counters_tried, _keys_per_sec| {
+                    *gpu_attempts.lock().unwrap() = counters_tried;
+                },
+            );
 
-                        // Update shared GPU attempts counter
-                        *gpu_attempts.lock().unwrap() = local_gpu_attempts;
-                    }
-                    Err(e) => {
-                        eprintln!("GPU error: {}", e);
-                        break;
+            match streamed_result {
+                Ok(Some((_found_counter, key_data))) => {
+                    let local_gpu_attempts = *gpu_attempts.lock().unwrap();
+                    if !found.swap(true, Ordering::Relaxed) {
+                        *result.lock().unwrap() = Some((
+                            hash_to_extension_id(&Sha256::digest(&key_data)),
+                            key_data,
+                            local_gpu_attempts,
+                            "GPU".to_string(),
+                        ));
                     }
                 }
+                Ok(None) => {
+                    // `found` was already set by a CPU thread; nothing to report.
+                }
+                Err(e) => {
+                    eprintln!("GPU error: {}", e);
+                }
             }
-
-            // Final update
-            *gpu_attempts.lock().unwrap() = local_gpu_attempts;
         })
     };
 
     // Spawn CPU threads
     let cpu_handles: Vec<_> = (0..num_cpu_threads)
         .map(|thread_id| {
-            let prefix = desired_prefix.to_string();
+            let criteria = criteria.clone();
             let found = Arc::clone(&found);
             let result = Arc::clone(&result);
             let start_time = start_time.clone();
             let last_progress_time = Arc::clone(&last_progress_time);
             let cpu_attempts = Arc::clone(&cpu_attempts);
             let gpu_attempts = Arc::clone(&gpu_attempts);
+            let next_batch = Arc::clone(&next_batch);
 
             thread::spawn(move || {
-                // Each CPU thread gets a range in the second half of counter space
-                let thread_start_counter =
-                    CPU_RANGE_START + ((thread_id as u64) * CPU_THREAD_RANGE_SIZE);
-                let mut local_counter = thread_start_counter;
+                let mut local_counter = next_batch.fetch_add(CPU_BATCH_SIZE, Ordering::Relaxed);
+                let mut batch_end = local_counter + CPU_BATCH_SIZE;
                 let mut local_attempts = 0u64;
                 const PROGRESS_REPORT_INTERVAL: u64 = 500000; // Report progress every 500k attempts in hybrid mode
 
                 while !found.load(Ordering::Relaxed) {
+                    if local_counter >= batch_end {
+                        local_counter = next_batch.fetch_add(CPU_BATCH_SIZE, Ordering::Relaxed);
+                        batch_end = local_counter + CPU_BATCH_SIZE;
+                    }
+
                     if let Some((ext_id, key_data)) =
-                        try_generate_match_optimized(&prefix, local_counter)
+                        try_generate_match_optimized(&criteria, local_counter)
                     {
                         if !found.swap(true, Ordering::Relaxed) {
                             *result.lock().unwrap() = Some((
@@ -456,11 +848,15 @@ fn run_hybrid_vanity_id_generator(
                                 if elapsed > 0.0 {
                                     let rate = total as f64 / elapsed;
                                     println!(
-                                        "Progress: {} attempts (GPU: {}, CPU: {}), {} keys/sec",
+                                        "Progress: {} attempts (GPU: {}, CPU: {}), {} keys/sec, ETA {}",
                                         total.to_formatted_string(&Locale::en),
                                         gpu_total.to_formatted_string(&Locale::en),
                                         cpu_total.to_formatted_string(&Locale::en),
-                                        (rate as u64).to_formatted_string(&Locale::en)
+                                        (rate as u64).to_formatted_string(&Locale::en),
+                                        criteria
+                                            .eta_secs(rate)
+                                            .map(format_eta)
+                                            .unwrap_or_else(|| "unknown".to_string())
                                     );
                                 }
                             }
@@ -523,10 +919,16 @@ fn run_hybrid_vanity_id_generator(
 }
 
 #[cfg(target_os = "macos")]
-fn run_gpu_vanity_id_generator(desired_prefix: &str, batch_size: u64) {
+fn run_gpu_vanity_id_generator(criteria: &MatchCriteria, batch_size: u64) {
     let start_time = Instant::now();
     let mut total_attempts = 0u64;
     let mut last_progress_time = Instant::now();
+    // Sum of per-batch GPU-measured compute time, kept separately from the
+    // CPU wall-clock `start_time` above so the progress line can show the
+    // true GPU throughput alongside the CPU-observed (dispatch + sync
+    // included) rate. Stays 0.0, and the GPU compute rate is left out of the
+    // progress line, on devices without a timestamp counter set.
+    let mut total_gpu_time = 0.0f64;
 
     // Initialize GPU
     let gpu = match GpuVanityGenerator::new() {
@@ -553,10 +955,13 @@ fn run_gpu_vanity_id_generator(desired_prefix: &str, batch_size: u64) {
         // Calculate starting counter for this batch using independent ranges
         let batch_start_counter = batch_id * batch_size;
 
-        match gpu.search_vanity_id(desired_prefix, batch_start_counter, batch_size) {
-            Ok(Some((found_counter, key_data))) => {
+        match gpu.search_vanity_id_timed(criteria, batch_start_counter, batch_size) {
+            Ok((Some((found_counter, key_data)), gpu_elapsed)) => {
                 // Found a match!
                 total_attempts += found_counter - batch_start_counter + 1;
+                if let Some(gpu_elapsed) = gpu_elapsed {
+                    total_gpu_time += gpu_elapsed;
+                }
 
                 let duration = start_time.elapsed().as_secs_f64();
                 let rate = total_attempts as f64 / duration;
@@ -573,9 +978,16 @@ fn run_gpu_vanity_id_generator(desired_prefix: &str, batch_size: u64) {
                 );
                 println!("Duration: {:.2} seconds", duration);
                 println!(
-                    "Rate: {} keys/second",
+                    "CPU-observed rate: {} keys/second",
                     (rate as u64).to_formatted_string(&Locale::en)
                 );
+                if total_gpu_time > 0.0 {
+                    let gpu_rate = total_attempts as f64 / total_gpu_time;
+                    println!(
+                        "GPU compute rate: {} keys/second",
+                        (gpu_rate as u64).to_formatted_string(&Locale::en)
+                    );
+                }
 
                 // Save files
                 save_key_files(&key_data);
@@ -586,10 +998,13 @@ fn run_gpu_vanity_id_generator(desired_prefix: &str, batch_size: u64) {
                 println!("{}", base64_key);
                 break;
             }
-            Ok(None) => {
+            Ok((None, gpu_elapsed)) => {
                 // No match in this batch, continue with next batch
                 total_attempts += batch_size;
                 batch_id += 1;
+                if let Some(gpu_elapsed) = gpu_elapsed {
+                    total_gpu_time += gpu_elapsed;
+                }
 
                 // Print progress every second
                 let now = Instant::now();
@@ -598,11 +1013,27 @@ fn run_gpu_vanity_id_generator(desired_prefix: &str, batch_size: u64) {
                     let elapsed = start_time.elapsed().as_secs_f64();
                     if elapsed > 0.0 {
                         let rate = total_attempts as f64 / elapsed;
-                        println!(
-                            "Progress: {} attempts, {} keys/sec",
-                            total_attempts.to_formatted_string(&Locale::en),
-                            (rate as u64).to_formatted_string(&Locale::en)
-                        );
+                        let eta = criteria
+                            .eta_secs(rate)
+                            .map(format_eta)
+                            .unwrap_or_else(|| "unknown".to_string());
+                        if total_gpu_time > 0.0 {
+                            let gpu_rate = total_attempts as f64 / total_gpu_time;
+                            println!(
+                                "Progress: {} attempts, {} keys/sec CPU-observed, {} keys/sec GPU compute, ETA {}",
+                                total_attempts.to_formatted_string(&Locale::en),
+                                (rate as u64).to_formatted_string(&Locale::en),
+                                (gpu_rate as u64).to_formatted_string(&Locale::en),
+                                eta
+                            );
+                        } else {
+                            println!(
+                                "Progress: {} attempts, {} keys/sec, ETA {}",
+                                total_attempts.to_formatted_string(&Locale::en),
+                                (rate as u64).to_formatted_string(&Locale::en),
+                                eta
+                            );
+                        }
                     }
                 }
             }
@@ -614,127 +1045,91 @@ fn run_gpu_vanity_id_generator(desired_prefix: &str, batch_size: u64) {
     }
 }
 
+/// Layers CPU threads on top of the multi-GPU CUDA scheduler
+/// (`cuda_multi::MultiGpuVanityGenerator`), so the search fans out across
+/// every CUDA device the host has instead of just the first one.
 #[cfg(feature = "cuda")]
 fn run_cuda_hybrid_vanity_id_generator(
-    desired_prefix: &str,
+    criteria: &MatchCriteria,
     num_cpu_threads: usize,
     gpu_batch_size: u64,
+    cuda_config: cuda_gpu::CudaConfig,
+    device_policy: Option<cuda_gpu::DevicePolicy>,
 ) {
     let start_time = Instant::now();
     let found = Arc::new(AtomicBool::new(false));
     let result = Arc::new(Mutex::new(None));
     let last_progress_time = Arc::new(Mutex::new(Instant::now()));
 
-    // Initialize CUDA GPU
-    let gpu = match CudaVanityGenerator::new() {
-        Ok(gpu) => {
-            println!("CUDA GPU Device: {}", gpu.get_device_name());
-            println!("Max threads per block: {}", gpu.get_max_threads_per_block());
-            println!(
-                "CUDA GPU batch size: {}",
-                gpu_batch_size.to_formatted_string(&Locale::en)
-            );
-            println!("CPU threads: {}", num_cpu_threads);
-            Some(gpu)
-        }
+    // Initialize every visible CUDA device, unless `device_policy` narrows
+    // this down to a single one.
+    let multi_gpu = match MultiGpuVanityGenerator::new(cuda_config, device_policy) {
+        Ok(gpu) => gpu,
         Err(e) => {
-            eprintln!("Failed to initialize CUDA GPU: {}", e);
+            eprintln!("Failed to initialize CUDA GPU(s): {}", e);
             std::process::exit(1);
         }
     };
+    let device_names = multi_gpu.device_names();
+    for (i, name) in device_names.iter().enumerate() {
+        println!("CUDA device {}: {}", i, name);
+    }
+    println!(
+        "CUDA GPU batch size (per device): {}",
+        gpu_batch_size.to_formatted_string(&Locale::en)
+    );
+    println!("CPU threads: {}", num_cpu_threads);
+
+    // Work dispenser shared by every CUDA device and every CPU thread: each
+    // claims its next range with `fetch_add(batch_size)` instead of the GPU
+    // pool and CPU pool being carved fixed, static halves of the counter
+    // space. CPU threads claim a much smaller batch than the GPU devices so
+    // a fast multi-GPU pool isn't stuck behind the CPU's claim size.
+    let next_batch = Arc::new(AtomicU64::new(0));
+    const CPU_BATCH_SIZE: u64 = 100_000;
+
+    let gpu_result: Arc<Mutex<Option<(u64, [u8; 32])>>> = Arc::new(Mutex::new(None));
+    let (gpu_handles, gpu_attempts) = multi_gpu.spawn(
+        criteria,
+        Arc::clone(&next_batch),
+        gpu_batch_size,
+        Arc::clone(&found),
+        Arc::clone(&gpu_result),
+    );
 
-    // Shared progress tracking for both GPU and CPU
-    let gpu_attempts = Arc::new(Mutex::new(0u64));
     let cpu_attempts = Arc::new(Mutex::new(vec![0u64; num_cpu_threads]));
 
-    // Counter range allocation:
-    // GPU gets the first half of the counter space (0 to u64::MAX/2)
-    // CPU threads get the second half (u64::MAX/2 to u64::MAX)
-    const GPU_RANGE_START: u64 = 0;
-    const CPU_RANGE_START: u64 = u64::MAX / 2;
-    const CPU_THREAD_RANGE_SIZE: u64 = (u64::MAX / 2) / 1024; // CPU threads share second half
-
-    // Spawn GPU thread
-    let gpu_handle = {
-        let prefix = desired_prefix.to_string();
-        let found = Arc::clone(&found);
-        let result = Arc::clone(&result);
-        let gpu_attempts = Arc::clone(&gpu_attempts);
-        let gpu = gpu.unwrap();
-
-        thread::spawn(move || {
-            let mut batch_id = 0u64;
-            let mut local_gpu_attempts = 0u64;
-
-            while !found.load(Ordering::Relaxed) {
-                // Calculate starting counter for this GPU batch
-                let batch_start_counter = GPU_RANGE_START + (batch_id * gpu_batch_size);
-
-                match gpu.search_vanity_id(&prefix, batch_start_counter, gpu_batch_size) {
-                    Ok(Some((found_counter, key_data))) => {
-                        // GPU found a match!
-                        local_gpu_attempts += found_counter - batch_start_counter + 1;
-
-                        if !found.swap(true, Ordering::Relaxed) {
-                            *result.lock().unwrap() = Some((
-                                hash_to_extension_id(&Sha256::digest(&key_data)),
-                                key_data,
-                                local_gpu_attempts,
-                                "CUDA GPU".to_string(),
-                            ));
-                        }
-                        break;
-                    }
-                    Ok(None) => {
-                        // No match in this batch, continue
-                        local_gpu_attempts += gpu_batch_size;
-                        batch_id += 1;
-
-                        // Update shared GPU attempts counter
-                        *gpu_attempts.lock().unwrap() = local_gpu_attempts;
-                    }
-                    Err(e) => {
-                        eprintln!("CUDA GPU error: {}", e);
-                        break;
-                    }
-                }
-            }
-
-            // Final update
-            *gpu_attempts.lock().unwrap() = local_gpu_attempts;
-        })
-    };
-
     // Spawn CPU threads (same as Metal hybrid implementation)
     let cpu_handles: Vec<_> = (0..num_cpu_threads)
         .map(|thread_id| {
-            let prefix = desired_prefix.to_string();
+            let criteria = criteria.clone();
             let found = Arc::clone(&found);
             let result = Arc::clone(&result);
             let start_time = start_time.clone();
             let last_progress_time = Arc::clone(&last_progress_time);
             let cpu_attempts = Arc::clone(&cpu_attempts);
             let gpu_attempts = Arc::clone(&gpu_attempts);
+            let device_names = device_names.clone();
+            let next_batch = Arc::clone(&next_batch);
 
             thread::spawn(move || {
-                // Each CPU thread gets a range in the second half of counter space
-                let thread_start_counter =
-                    CPU_RANGE_START + ((thread_id as u64) * CPU_THREAD_RANGE_SIZE);
-                let mut local_counter = thread_start_counter;
+                let mut local_counter = next_batch.fetch_add(CPU_BATCH_SIZE, Ordering::Relaxed);
+                let mut batch_end = local_counter + CPU_BATCH_SIZE;
                 let mut local_attempts = 0u64;
                 const PROGRESS_REPORT_INTERVAL: u64 = 500000; // Report progress every 500k attempts in hybrid mode
 
                 while !found.load(Ordering::Relaxed) {
+                    if local_counter >= batch_end {
+                        local_counter = next_batch.fetch_add(CPU_BATCH_SIZE, Ordering::Relaxed);
+                        batch_end = local_counter + CPU_BATCH_SIZE;
+                    }
+
                     if let Some((ext_id, key_data)) =
-                        try_generate_match_optimized(&prefix, local_counter)
+                        try_generate_match_optimized(&criteria, local_counter)
                     {
                         if !found.swap(true, Ordering::Relaxed) {
-                            *result.lock().unwrap() = Some((
-                                ext_id,
-                                key_data,
-                                local_attempts + 1,
-                                format!("CPU-{}", thread_id),
-                            ));
+                            *result.lock().unwrap() =
+                                Some((ext_id, key_data, format!("CPU-{}", thread_id)));
                         }
                         break;
                     }
@@ -757,8 +1152,9 @@ fn run_cuda_hybrid_vanity_id_generator(
                             if now.duration_since(*last_time).as_secs() >= 1 {
                                 *last_time = now;
 
-                                // Calculate total attempts across GPU and all CPU threads
-                                let gpu_total = *gpu_attempts.lock().unwrap();
+                                // Calculate total attempts across every GPU device and all CPU threads
+                                let per_device_totals = gpu_attempts.lock().unwrap().clone();
+                                let gpu_total: u64 = per_device_totals.iter().sum();
                                 let cpu_total = {
                                     let attempts = cpu_attempts.lock().unwrap();
                                     attempts.iter().sum::<u64>()
@@ -768,13 +1164,33 @@ fn run_cuda_hybrid_vanity_id_generator(
                                 let elapsed = start_time.elapsed().as_secs_f64();
                                 if elapsed > 0.0 {
                                     let rate = total as f64 / elapsed;
+                                    let per_device = device_names
+                                        .iter()
+                                        .zip(per_device_totals.iter())
+                                        .enumerate()
+                                        .map(|(i, (_name, attempts))| {
+                                            let device_rate = *attempts as f64 / elapsed;
+                                            format!(
+                                                "GPU{}: {} ({} keys/sec)",
+                                                i,
+                                                attempts.to_formatted_string(&Locale::en),
+                                                (device_rate as u64).to_formatted_string(&Locale::en)
+                                            )
+                                        })
+                                        .collect::<Vec<_>>()
+                                        .join(", ");
                                     println!(
-                                        "Progress: {} attempts (CUDA GPU: {}, CPU: {}), {} keys/sec",
+                                        "Progress: {} attempts ({}, CPU: {}), {} keys/sec, ETA {}",
                                         total.to_formatted_string(&Locale::en),
-                                        gpu_total.to_formatted_string(&Locale::en),
+                                        per_device,
                                         cpu_total.to_formatted_string(&Locale::en),
-                                        (rate as u64).to_formatted_string(&Locale::en)
+                                        (rate as u64).to_formatted_string(&Locale::en),
+                                        criteria
+                                            .eta_secs(rate)
+                                            .map(format_eta)
+                                            .unwrap_or_else(|| "unknown".to_string())
                                     );
+                                    print_probability_progress(&criteria, total, rate);
                                 }
                             }
                         }
@@ -790,19 +1206,30 @@ fn run_cuda_hybrid_vanity_id_generator(
         })
         .collect();
 
-    // Wait for completion (either GPU or CPU finds a match)
-    gpu_handle.join().unwrap();
+    // Wait for completion (either a GPU device or CPU finds a match)
+    for handle in gpu_handles {
+        handle.join().unwrap();
+    }
     for handle in cpu_handles {
         handle.join().unwrap();
     }
 
-    // Output results
-    let result_data = result.lock().unwrap().take();
-    if let Some((ext_id, key_data, _winning_attempts, winner)) = result_data {
+    // Output results: whichever side actually recorded a match (only one can,
+    // since both race on the same `found` flag).
+    let final_result = match gpu_result.lock().unwrap().take() {
+        Some((_counter, key_data)) => Some((
+            hash_to_extension_id(&Sha256::digest(&key_data)),
+            key_data,
+            "CUDA GPU".to_string(),
+        )),
+        None => result.lock().unwrap().take(),
+    };
+
+    if let Some((ext_id, key_data, winner)) = final_result {
         let duration = start_time.elapsed().as_secs_f64();
 
-        // Calculate total attempts across GPU and all CPU threads
-        let gpu_total = *gpu_attempts.lock().unwrap();
+        // Calculate total attempts across every GPU device and all CPU threads
+        let gpu_total: u64 = gpu_attempts.lock().unwrap().iter().sum();
         let cpu_total = {
             let attempts = cpu_attempts.lock().unwrap();
             attempts.iter().sum::<u64>()
@@ -835,17 +1262,252 @@ fn run_cuda_hybrid_vanity_id_generator(
     }
 }
 
+/// Fans the search across every CUDA device the host has via
+/// `cuda_multi::MultiGpuVanityGenerator`, rather than just the first one.
+/// With a single device this behaves exactly like the old one-GPU loop.
 #[cfg(feature = "cuda")]
-fn run_cuda_vanity_id_generator(desired_prefix: &str, batch_size: u64) {
+fn run_cuda_vanity_id_generator(
+    criteria: &MatchCriteria,
+    batch_size: u64,
+    cuda_config: cuda_gpu::CudaConfig,
+    device_policy: Option<cuda_gpu::DevicePolicy>,
+) {
+    let start_time = Instant::now();
+    let mut last_progress_time = Instant::now();
+
+    let multi_gpu = match MultiGpuVanityGenerator::new(cuda_config, device_policy) {
+        Ok(gpu) => gpu,
+        Err(e) => {
+            eprintln!("Failed to initialize CUDA GPU(s): {}", e);
+            std::process::exit(1);
+        }
+    };
+    let device_names = multi_gpu.device_names();
+    for (i, name) in device_names.iter().enumerate() {
+        println!("CUDA device {}: {}", i, name);
+    }
+    println!(
+        "Batch size (per device): {}",
+        batch_size.to_formatted_string(&Locale::en)
+    );
+
+    let found = Arc::new(AtomicBool::new(false));
+    let result = Arc::new(Mutex::new(None));
+    let next_batch = Arc::new(AtomicU64::new(0));
+    let (handles, device_attempts) = multi_gpu.spawn(
+        criteria,
+        next_batch,
+        batch_size,
+        Arc::clone(&found),
+        Arc::clone(&result),
+    );
+
+    while !found.load(Ordering::Relaxed) {
+        thread::sleep(std::time::Duration::from_millis(100));
+
+        let now = Instant::now();
+        if now.duration_since(last_progress_time).as_secs() >= 1 {
+            last_progress_time = now;
+            let per_device_totals = device_attempts.lock().unwrap().clone();
+            let total: u64 = per_device_totals.iter().sum();
+            let elapsed = start_time.elapsed().as_secs_f64();
+            if elapsed > 0.0 {
+                let rate = total as f64 / elapsed;
+                let per_device = per_device_totals
+                    .iter()
+                    .enumerate()
+                    .map(|(i, attempts)| {
+                        let device_rate = *attempts as f64 / elapsed;
+                        format!(
+                            "GPU{}: {} ({} keys/sec)",
+                            i,
+                            attempts.to_formatted_string(&Locale::en),
+                            (device_rate as u64).to_formatted_string(&Locale::en)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                println!(
+                    "Progress: {} attempts ({}), {} keys/sec, ETA {}",
+                    total.to_formatted_string(&Locale::en),
+                    per_device,
+                    (rate as u64).to_formatted_string(&Locale::en),
+                    criteria
+                        .eta_secs(rate)
+                        .map(format_eta)
+                        .unwrap_or_else(|| "unknown".to_string())
+                );
+                print_probability_progress(criteria, total, rate);
+            }
+        }
+    }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    if let Some((_found_counter, key_data)) = result.lock().unwrap().take() {
+        let total_attempts: u64 = device_attempts.lock().unwrap().iter().sum();
+        let duration = start_time.elapsed().as_secs_f64();
+        let rate = total_attempts as f64 / duration;
+
+        let hash = Sha256::digest(&key_data);
+        let extension_id = hash_to_extension_id(&hash);
+
+        println!("\n🎉 Match found!");
+        println!("Extension ID: {}", extension_id);
+        println!(
+            "Total attempts: {}",
+            total_attempts.to_formatted_string(&Locale::en)
+        );
+        println!("Duration: {:.2} seconds", duration);
+        println!(
+            "Rate: {} keys/second",
+            (rate as u64).to_formatted_string(&Locale::en)
+        );
+
+        save_key_files(&key_data);
+
+        let base64_key = base64::engine::general_purpose::STANDARD.encode(&key_data);
+        println!("\nPublic key for manifest.json:");
+        println!("{}", base64_key);
+    }
+}
+
+/// Prints why the requested backend can't run in this build and exits, the
+/// same way the CUDA/Metal init failure paths already do.
+fn backend_unavailable(name: &str, reason: &str) {
+    eprintln!("{} backend unavailable: {}", name, reason);
+    std::process::exit(1);
+}
+
+/// Times every backend available in this build (CPU, Metal/CUDA/OpenCl if
+/// compiled in and a device is present, plus hybrid for whichever native GPU
+/// backend is available) for `seconds`, then prints a comparison table and
+/// optionally writes it to `benchmark_json` as JSON.
+fn run_benchmark(
+    seconds: u64,
+    gpu_batch_size: u64,
+    benchmark_json: Option<&str>,
+    cores: usize,
+    single_thread: bool,
+) {
+    let duration = std::time::Duration::from_secs(seconds);
+    let cpu_threads = if single_thread {
+        1
+    } else {
+        std::cmp::max(1, cores / 4)
+    };
+    let mut results = Vec::new();
+
+    println!(
+        "Running {}-second benchmark per backend against an unmatchable target...",
+        seconds
+    );
+
+    let cpu = backend::CpuVanityGenerator::new();
+    let cpu_batch_size = cpu.preferred_batch_size();
+    results.push(benchmark::benchmark_backend(
+        "CPU",
+        &cpu,
+        cpu_batch_size,
+        duration,
+    ));
+
+    #[cfg(target_os = "macos")]
+    {
+        match GpuVanityGenerator::new() {
+            Ok(gpu) => {
+                results.push(benchmark::benchmark_backend(
+                    "Metal",
+                    &gpu,
+                    gpu_batch_size,
+                    duration,
+                ));
+                results.push(benchmark::benchmark_hybrid(
+                    &gpu,
+                    gpu_batch_size,
+                    cpu_threads,
+                    duration,
+                ));
+            }
+            Err(e) => println!("Metal backend unavailable, skipping: {}", e),
+        }
+    }
+
+    #[cfg(feature = "cuda")]
+    {
+        match cuda_gpu::CudaVanityGenerator::new() {
+            Ok(gpu) => {
+                results.push(benchmark::benchmark_backend(
+                    "CUDA",
+                    &gpu,
+                    gpu_batch_size,
+                    duration,
+                ));
+                results.push(benchmark::benchmark_hybrid(
+                    &gpu,
+                    gpu_batch_size,
+                    cpu_threads,
+                    duration,
+                ));
+            }
+            Err(e) => println!("CUDA backend unavailable, skipping: {}", e),
+        }
+
+        match MultiGpuVanityGenerator::new(cuda_gpu::CudaConfig::default(), None) {
+            Ok(multi_gpu) if multi_gpu.device_count() > 1 => {
+                results.push(benchmark::benchmark_multi_cuda(
+                    multi_gpu,
+                    gpu_batch_size,
+                    duration,
+                ));
+            }
+            Ok(_) => {} // Only one CUDA device: already covered by the single-GPU row above.
+            Err(e) => println!("CUDA multi-GPU benchmark unavailable, skipping: {}", e),
+        }
+    }
+
+    #[cfg(feature = "opencl")]
+    {
+        match OpenClVanityGenerator::new() {
+            Ok(gpu) => {
+                results.push(benchmark::benchmark_backend(
+                    "OpenCL",
+                    &gpu,
+                    gpu_batch_size,
+                    duration,
+                ));
+                results.push(benchmark::benchmark_hybrid(
+                    &gpu,
+                    gpu_batch_size,
+                    cpu_threads,
+                    duration,
+                ));
+            }
+            Err(e) => println!("OpenCL backend unavailable, skipping: {}", e),
+        }
+    }
+
+    benchmark::print_table(&results);
+
+    if let Some(path) = benchmark_json {
+        match benchmark::write_json(&results, path) {
+            Ok(()) => println!("\nWrote benchmark results to {}", path),
+            Err(e) => eprintln!("Failed to write {}: {}", path, e),
+        }
+    }
+}
+
+#[cfg(feature = "opencl")]
+fn run_opencl_vanity_id_generator(criteria: &MatchCriteria, batch_size: u64) {
     let start_time = Instant::now();
     let mut total_attempts = 0u64;
     let mut last_progress_time = Instant::now();
 
-    // Initialize CUDA GPU
-    let gpu = match CudaVanityGenerator::new() {
+    let gpu = match OpenClVanityGenerator::new() {
         Ok(gpu) => {
-            println!("CUDA GPU Device: {}", gpu.get_device_name());
-            println!("Max threads per block: {}", gpu.get_max_threads_per_block());
+            println!("OpenCL GPU Device: {}", gpu.get_device_name());
             println!(
                 "Batch size: {}",
                 batch_size.to_formatted_string(&Locale::en)
@@ -853,28 +1515,24 @@ fn run_cuda_vanity_id_generator(desired_prefix: &str, batch_size: u64) {
             gpu
         }
         Err(e) => {
-            eprintln!("Failed to initialize CUDA GPU: {}", e);
+            eprintln!("Failed to initialize OpenCL GPU: {}", e);
             std::process::exit(1);
         }
     };
 
-    // Use independent counter ranges like CPU implementation
-    // Each batch gets a unique range to avoid overlap with other potential GPU instances
+    // Use independent counter ranges like the CUDA/CPU implementations.
     let mut batch_id = 0u64;
 
     loop {
-        // Calculate starting counter for this batch using independent ranges
         let batch_start_counter = batch_id * batch_size;
 
-        match gpu.search_vanity_id(desired_prefix, batch_start_counter, batch_size) {
+        match gpu.search_vanity_id(criteria, batch_start_counter, batch_size) {
             Ok(Some((found_counter, key_data))) => {
-                // Found a match!
                 total_attempts += found_counter - batch_start_counter + 1;
 
                 let duration = start_time.elapsed().as_secs_f64();
                 let rate = total_attempts as f64 / duration;
 
-                // Generate extension ID for display
                 let hash = Sha256::digest(&key_data);
                 let extension_id = hash_to_extension_id(&hash);
 
@@ -890,21 +1548,17 @@ fn run_cuda_vanity_id_generator(desired_prefix: &str, batch_size: u64) {
                     (rate as u64).to_formatted_string(&Locale::en)
                 );
 
-                // Save files
                 save_key_files(&key_data);
 
-                // Print base64 for manifest
                 let base64_key = base64::engine::general_purpose::STANDARD.encode(&key_data);
                 println!("\nPublic key for manifest.json:");
                 println!("{}", base64_key);
                 break;
             }
             Ok(None) => {
-                // No match in this batch, continue with next batch
                 total_attempts += batch_size;
                 batch_id += 1;
 
-                // Print progress every second
                 let now = Instant::now();
                 if now.duration_since(last_progress_time).as_secs() >= 1 {
                     last_progress_time = now;
@@ -912,21 +1566,234 @@ fn run_cuda_vanity_id_generator(desired_prefix: &str, batch_size: u64) {
                     if elapsed > 0.0 {
                         let rate = total_attempts as f64 / elapsed;
                         println!(
-                            "Progress: {} attempts, {} keys/sec",
+                            "Progress: {} attempts, {} keys/sec, ETA {}",
                             total_attempts.to_formatted_string(&Locale::en),
-                            (rate as u64).to_formatted_string(&Locale::en)
+                            (rate as u64).to_formatted_string(&Locale::en),
+                            criteria
+                                .eta_secs(rate)
+                                .map(format_eta)
+                                .unwrap_or_else(|| "unknown".to_string())
                         );
                     }
                 }
             }
             Err(e) => {
-                eprintln!("CUDA GPU error: {}", e);
+                eprintln!("OpenCL GPU error: {}", e);
                 std::process::exit(1);
             }
         }
     }
 }
 
+#[cfg(feature = "opencl")]
+fn run_opencl_hybrid_vanity_id_generator(
+    criteria: &MatchCriteria,
+    num_cpu_threads: usize,
+    gpu_batch_size: u64,
+) {
+    let start_time = Instant::now();
+    let found = Arc::new(AtomicBool::new(false));
+    let result = Arc::new(Mutex::new(None));
+    let last_progress_time = Arc::new(Mutex::new(Instant::now()));
+
+    let gpu = match OpenClVanityGenerator::new() {
+        Ok(gpu) => {
+            println!("OpenCL GPU Device: {}", gpu.get_device_name());
+            println!(
+                "OpenCL GPU batch size: {}",
+                gpu_batch_size.to_formatted_string(&Locale::en)
+            );
+            println!("CPU threads: {}", num_cpu_threads);
+            Some(gpu)
+        }
+        Err(e) => {
+            eprintln!("Failed to initialize OpenCL GPU: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Shared progress tracking for both GPU and CPU
+    let gpu_attempts = Arc::new(Mutex::new(0u64));
+    let cpu_attempts = Arc::new(Mutex::new(vec![0u64; num_cpu_threads]));
+
+    // Work dispenser shared between the GPU thread and every CPU thread, in
+    // place of a fixed GPU-gets-first-half/CPU-gets-second-half split: both
+    // sides claim their next range with `fetch_add(batch_size)`, so nothing
+    // is wasted if one side runs faster than the other. CPU threads claim a
+    // much smaller batch than the GPU.
+    let next_batch = Arc::new(AtomicU64::new(0));
+    const CPU_BATCH_SIZE: u64 = 100_000;
+
+    let gpu_handle = {
+        let criteria = criteria.clone();
+        let found = Arc::clone(&found);
+        let result = Arc::clone(&result);
+        let gpu_attempts = Arc::clone(&gpu_attempts);
+        let next_batch = Arc::clone(&next_batch);
+        let gpu = gpu.unwrap();
+
+        thread::spawn(move || {
+            let mut local_gpu_attempts = 0u64;
+
+            while !found.load(Ordering::Relaxed) {
+                let batch_start_counter = next_batch.fetch_add(gpu_batch_size, Ordering::Relaxed);
+
+                match gpu.search_vanity_id(&criteria, batch_start_counter, gpu_batch_size) {
+                    Ok(Some((found_counter, key_data))) => {
+                        local_gpu_attempts += found_counter - batch_start_counter + 1;
+
+                        if !found.swap(true, Ordering::Relaxed) {
+                            *result.lock().unwrap() = Some((
+                                hash_to_extension_id(&Sha256::digest(&key_data)),
+                                key_data,
+                                local_gpu_attempts,
+                                "OpenCL GPU".to_string(),
+                            ));
+                        }
+                        break;
+                    }
+                    Ok(None) => {
+                        local_gpu_attempts += gpu_batch_size;
+                        *gpu_attempts.lock().unwrap() = local_gpu_attempts;
+                    }
+                    Err(e) => {
+                        eprintln!("OpenCL GPU error: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            *gpu_attempts.lock().unwrap() = local_gpu_attempts;
+        })
+    };
+
+    let cpu_handles: Vec<_> = (0..num_cpu_threads)
+        .map(|thread_id| {
+            let criteria = criteria.clone();
+            let found = Arc::clone(&found);
+            let result = Arc::clone(&result);
+            let start_time = start_time.clone();
+            let last_progress_time = Arc::clone(&last_progress_time);
+            let cpu_attempts = Arc::clone(&cpu_attempts);
+            let gpu_attempts = Arc::clone(&gpu_attempts);
+            let next_batch = Arc::clone(&next_batch);
+
+            thread::spawn(move || {
+                let mut local_counter = next_batch.fetch_add(CPU_BATCH_SIZE, Ordering::Relaxed);
+                let mut batch_end = local_counter + CPU_BATCH_SIZE;
+                let mut local_attempts = 0u64;
+                const PROGRESS_REPORT_INTERVAL: u64 = 500000;
+
+                while !found.load(Ordering::Relaxed) {
+                    if local_counter >= batch_end {
+                        local_counter = next_batch.fetch_add(CPU_BATCH_SIZE, Ordering::Relaxed);
+                        batch_end = local_counter + CPU_BATCH_SIZE;
+                    }
+
+                    if let Some((ext_id, key_data)) =
+                        try_generate_match_optimized(&criteria, local_counter)
+                    {
+                        if !found.swap(true, Ordering::Relaxed) {
+                            *result.lock().unwrap() = Some((
+                                ext_id,
+                                key_data,
+                                local_attempts + 1,
+                                format!("CPU-{}", thread_id),
+                            ));
+                        }
+                        break;
+                    }
+
+                    local_counter += 1;
+                    local_attempts += 1;
+
+                    if local_attempts % PROGRESS_REPORT_INTERVAL == 0 {
+                        {
+                            let mut attempts = cpu_attempts.lock().unwrap();
+                            attempts[thread_id] = local_attempts;
+                        }
+
+                        if thread_id == 0 {
+                            let now = Instant::now();
+                            let mut last_time = last_progress_time.lock().unwrap();
+                            if now.duration_since(*last_time).as_secs() >= 1 {
+                                *last_time = now;
+
+                                let gpu_total = *gpu_attempts.lock().unwrap();
+                                let cpu_total = {
+                                    let attempts = cpu_attempts.lock().unwrap();
+                                    attempts.iter().sum::<u64>()
+                                };
+                                let total = gpu_total + cpu_total;
+
+                                let elapsed = start_time.elapsed().as_secs_f64();
+                                if elapsed > 0.0 {
+                                    let rate = total as f64 / elapsed;
+                                    println!(
+                                        "Progress: {} attempts (OpenCL GPU: {}, CPU: {}), {} keys/sec, ETA {}",
+                                        total.to_formatted_string(&Locale::en),
+                                        gpu_total.to_formatted_string(&Locale::en),
+                                        cpu_total.to_formatted_string(&Locale::en),
+                                        (rate as u64).to_formatted_string(&Locale::en),
+                                        criteria
+                                            .eta_secs(rate)
+                                            .map(format_eta)
+                                            .unwrap_or_else(|| "unknown".to_string())
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
+
+                {
+                    let mut attempts = cpu_attempts.lock().unwrap();
+                    attempts[thread_id] = local_attempts;
+                }
+            })
+        })
+        .collect();
+
+    gpu_handle.join().unwrap();
+    for handle in cpu_handles {
+        handle.join().unwrap();
+    }
+
+    let result_data = result.lock().unwrap().take();
+    if let Some((ext_id, key_data, _winning_attempts, winner)) = result_data {
+        let duration = start_time.elapsed().as_secs_f64();
+
+        let gpu_total = *gpu_attempts.lock().unwrap();
+        let cpu_total = {
+            let attempts = cpu_attempts.lock().unwrap();
+            attempts.iter().sum::<u64>()
+        };
+        let total = gpu_total + cpu_total;
+
+        let rate = total as f64 / duration;
+
+        println!("\n🎉 Match found by {}!", winner);
+        println!("Extension ID: {}", ext_id);
+        println!(
+            "Total attempts: {} (OpenCL GPU: {}, CPU: {})",
+            total.to_formatted_string(&Locale::en),
+            gpu_total.to_formatted_string(&Locale::en),
+            cpu_total.to_formatted_string(&Locale::en)
+        );
+        println!("Duration: {:.2} seconds", duration);
+        println!(
+            "Rate: {} keys/second",
+            (rate as u64).to_formatted_string(&Locale::en)
+        );
+
+        save_key_files(&key_data);
+
+        let base64_key = base64::engine::general_purpose::STANDARD.encode(&key_data);
+        println!("\nPublic key for manifest.json:");
+        println!("{}", base64_key);
+    }
+}
+
 fn save_key_files(key_data: &[u8]) {
     // Save DER format
     std::fs::write("public_key.der", key_data).expect("Failed to write DER file");
@@ -0,0 +1,391 @@
+//! Compiles the CLI's match flags (`--prefix`, `--suffix`, `--contains`,
+//! `--pattern`, `--leading`) into a single `MatchCriteria`, once, so the hot
+//! hash loop (and every GPU kernel) evaluates one cheap precomputed check
+//! instead of re-parsing a pattern string per attempt.
+//!
+//! Every criterion boils down to the same shape: a run of nibble positions,
+//! each constrained to a 16-bit bitmask of the nibble values (0..16, mapped
+//! to `a`..`p` by `MAPPING`) that are allowed there, anchored at the start of
+//! the ID, the end, or slid across every offset (`--contains`). That shape is
+//! cheap on the CPU and trivial to mirror into the CUDA/OpenCL kernels: see
+//! `gpu_spec()`.
+
+use crate::MAPPING;
+
+/// Number of nibble characters in a generated extension ID (16 hash bytes,
+/// 2 characters per byte — see `hash_to_extension_id`).
+pub const ID_NIBBLES: usize = 32;
+
+/// A 16-bit bitmask with every nibble value (0..16) allowed: matches any
+/// character, used for `.` in `--pattern`.
+const WILDCARD_MASK: u16 = 0xFFFF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    Start,
+    End,
+    /// Slide the template across every valid offset (`--contains`).
+    Anywhere,
+}
+
+impl Anchor {
+    /// Matches the `anchor` encoding read by `criteria_matches` in
+    /// vanity_id.cu/vanity_id.cl — keep these in sync.
+    fn code(self) -> u32 {
+        match self {
+            Anchor::Start => 0,
+            Anchor::End => 1,
+            Anchor::Anywhere => 2,
+        }
+    }
+}
+
+/// A compiled match target. Cheap to evaluate (`matches`) and cheap to hand
+/// to a GPU kernel (`gpu_spec`) since both boil down to the same
+/// mask-per-position template.
+#[derive(Clone)]
+pub struct MatchCriteria {
+    description: String,
+    anchor: Anchor,
+    /// `masks[i]` is the 16-bit bitmask of nibble values allowed at template
+    /// position `i`. A single set bit is an exact character match; all 16
+    /// bits set (`WILDCARD_MASK`) is `.`; anything else is a character class.
+    masks: Vec<u16>,
+    /// Number of template positions that constrain something (i.e. aren't a
+    /// full wildcard) — drives the `16^constrained_nibbles` attempt estimate.
+    constrained_nibbles: u32,
+}
+
+impl MatchCriteria {
+    /// `--prefix`: the ID's first `prefix.len()` characters must match
+    /// literally.
+    pub fn prefix(prefix: &str) -> Result<Self, String> {
+        let masks = literal_masks(prefix)?;
+        Ok(MatchCriteria {
+            description: format!("prefix \"{}\"", prefix),
+            anchor: Anchor::Start,
+            constrained_nibbles: masks.len() as u32,
+            masks,
+        })
+    }
+
+    /// `--suffix`: the ID's last `suffix.len()` characters must match
+    /// literally.
+    pub fn suffix(suffix: &str) -> Result<Self, String> {
+        let masks = literal_masks(suffix)?;
+        Ok(MatchCriteria {
+            description: format!("suffix \"{}\"", suffix),
+            anchor: Anchor::End,
+            constrained_nibbles: masks.len() as u32,
+            masks,
+        })
+    }
+
+    /// `--contains`: `needle` must appear literally somewhere in the ID.
+    pub fn contains(needle: &str) -> Result<Self, String> {
+        let masks = literal_masks(needle)?;
+        Ok(MatchCriteria {
+            description: format!("\"{}\" anywhere in the ID", needle),
+            anchor: Anchor::Anywhere,
+            constrained_nibbles: masks.len() as u32,
+            masks,
+        })
+    }
+
+    /// `--pattern`: a fixed-length template anchored at the start of the ID.
+    /// `.` matches any character; `[...]` matches a character class, e.g.
+    /// `[a-f]` (a range) or `[ace]` (a list); anything else must match
+    /// literally.
+    pub fn pattern(pattern: &str) -> Result<Self, String> {
+        let masks = parse_pattern(pattern)?;
+        Ok(MatchCriteria {
+            description: format!("pattern \"{}\"", pattern),
+            anchor: Anchor::Start,
+            constrained_nibbles: masks
+                .iter()
+                .filter(|&&mask| mask != WILDCARD_MASK)
+                .count() as u32,
+            masks,
+        })
+    }
+
+    /// `--leading`: require `count` leading `a` characters (the zero-nibble),
+    /// the same "leading zero run" idea PoW miners target with a difficulty
+    /// target.
+    pub fn leading(count: usize) -> Self {
+        MatchCriteria {
+            description: format!("{} leading 'a' character(s)", count),
+            anchor: Anchor::Start,
+            masks: vec![1u16; count],
+            constrained_nibbles: count as u32,
+        }
+    }
+
+    /// A criterion that can never match: used by `--benchmark` to measure
+    /// sustained throughput without a real search ever finding (and
+    /// short-circuiting) a batch.
+    pub fn unmatchable() -> Self {
+        MatchCriteria {
+            description: "unmatchable (benchmark)".to_string(),
+            anchor: Anchor::Start,
+            masks: vec![0u16],
+            constrained_nibbles: 1,
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Whether `hash` (a 32-byte SHA-256 digest, only the first 16 bytes of
+    /// which become the 32-character ID) satisfies this criterion.
+    pub fn matches(&self, hash: &[u8]) -> bool {
+        let len = self.masks.len();
+        if len == 0 {
+            return true;
+        }
+        match self.anchor {
+            Anchor::Start => matches_at(hash, 0, &self.masks),
+            Anchor::End => matches_at(hash, ID_NIBBLES - len, &self.masks),
+            Anchor::Anywhere => (0..=ID_NIBBLES - len).any(|start| matches_at(hash, start, &self.masks)),
+        }
+    }
+
+    /// Expected number of attempts before a random hash satisfies this
+    /// criterion: `16^constrained_nibbles`, since each constrained nibble
+    /// independently narrows the space by a factor of 16. Saturates at
+    /// `u128::MAX` rather than overflowing for pathological patterns.
+    pub fn expected_attempts(&self) -> u128 {
+        16u128
+            .checked_pow(self.constrained_nibbles)
+            .unwrap_or(u128::MAX)
+    }
+
+    /// Estimated time to a match at `keys_per_sec`, or `None` if the rate
+    /// isn't yet known (e.g. before the first progress tick).
+    pub fn eta_secs(&self, keys_per_sec: f64) -> Option<f64> {
+        if keys_per_sec <= 0.0 {
+            None
+        } else {
+            Some(self.expected_attempts() as f64 / keys_per_sec)
+        }
+    }
+
+    /// Per-attempt probability of a random hash satisfying this criterion:
+    /// `16^-constrained_nibbles`, the reciprocal of `expected_attempts`.
+    fn match_probability(&self) -> f64 {
+        16f64.powi(-(self.constrained_nibbles as i32))
+    }
+
+    /// Median time to a match at `keys_per_sec`: the point at which a
+    /// geometric search has a 50% chance of having already succeeded,
+    /// `ln(2) * expected_attempts / keys_per_sec`. Unlike `eta_secs` (the
+    /// mean), this is the number a user watching the search is actually
+    /// asking when they want to know "when will this land" — the mean of an
+    /// exponential-ish wait is skewed upward by its long tail. `None` if the
+    /// rate isn't yet known.
+    pub fn median_eta_secs(&self, keys_per_sec: f64) -> Option<f64> {
+        if keys_per_sec <= 0.0 {
+            None
+        } else {
+            Some(std::f64::consts::LN_2 * self.expected_attempts() as f64 / keys_per_sec)
+        }
+    }
+
+    /// Probability of having already found a match after `attempts` tries:
+    /// `1 - (1 - p)^attempts`, where `p` is `match_probability()`.
+    pub fn cumulative_probability(&self, attempts: u64) -> f64 {
+        1.0 - (1.0 - self.match_probability()).powf(attempts as f64)
+    }
+
+    /// Serializes this criterion for the GPU kernels: `[anchor, len, mask_0,
+    /// mask_1, ..., mask_{len-1}]`, one `u32` per word. Mirrors
+    /// `criteria_matches` in vanity_id.cu/vanity_id.cl — keep them in sync.
+    pub fn gpu_spec(&self) -> Vec<u32> {
+        let mut spec = Vec::with_capacity(2 + self.masks.len());
+        spec.push(self.anchor.code());
+        spec.push(self.masks.len() as u32);
+        spec.extend(self.masks.iter().map(|&mask| mask as u32));
+        spec
+    }
+}
+
+fn matches_at(hash: &[u8], start: usize, masks: &[u16]) -> bool {
+    for (i, &mask) in masks.iter().enumerate() {
+        let nibble = nibble_at(hash, start + i);
+        if mask & (1u16 << nibble) == 0 {
+            return false;
+        }
+    }
+    true
+}
+
+fn nibble_at(hash: &[u8], i: usize) -> u8 {
+    let byte = hash[i / 2];
+    if i % 2 == 0 {
+        byte >> 4
+    } else {
+        byte & 0x0F
+    }
+}
+
+/// Converts a literal string in the ID's `a`..`p` alphabet into one
+/// exact-match mask per character.
+fn literal_masks(s: &str) -> Result<Vec<u16>, String> {
+    if s.len() > ID_NIBBLES {
+        return Err(format!(
+            "\"{}\" is {} characters long but the extension ID is only {} characters",
+            s,
+            s.len(),
+            ID_NIBBLES
+        ));
+    }
+    s.chars().map(|c| char_to_nibble(c).map(|n| 1u16 << n)).collect()
+}
+
+fn parse_pattern(pattern: &str) -> Result<Vec<u16>, String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut masks = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                masks.push(WILDCARD_MASK);
+                i += 1;
+            }
+            '[' => {
+                let close = chars[i..]
+                    .iter()
+                    .position(|&c| c == ']')
+                    .ok_or_else(|| format!("unterminated '[' in pattern \"{}\"", pattern))?;
+                masks.push(parse_class(&chars[i + 1..i + close])?);
+                i += close + 1;
+            }
+            c => {
+                masks.push(1u16 << char_to_nibble(c)?);
+                i += 1;
+            }
+        }
+    }
+    if masks.len() > ID_NIBBLES {
+        return Err(format!(
+            "pattern \"{}\" constrains {} characters but the extension ID is only {} characters",
+            pattern,
+            masks.len(),
+            ID_NIBBLES
+        ));
+    }
+    Ok(masks)
+}
+
+/// Parses the contents of a `[...]` character class: a list of characters
+/// and/or `x-y` ranges, e.g. `a-f`, `ace`, `a-cj`.
+fn parse_class(class: &[char]) -> Result<u16, String> {
+    let mut mask = 0u16;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            let lo = char_to_nibble(class[i])?;
+            let hi = char_to_nibble(class[i + 2])?;
+            if lo > hi {
+                return Err(format!("invalid character range {}-{}", class[i], class[i + 2]));
+            }
+            for n in lo..=hi {
+                mask |= 1u16 << n;
+            }
+            i += 3;
+        } else {
+            mask |= 1u16 << char_to_nibble(class[i])?;
+            i += 1;
+        }
+    }
+    if mask == 0 {
+        return Err("empty character class \"[]\"".to_string());
+    }
+    Ok(mask)
+}
+
+fn char_to_nibble(c: char) -> Result<u8, String> {
+    MAPPING
+        .iter()
+        .position(|&m| m == c)
+        .map(|n| n as u8)
+        .ok_or_else(|| format!("'{}' is not in the extension-id alphabet ('a'..'p')", c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_for(id: &str) -> [u8; 32] {
+        // Inverse of hash_to_extension_id: pack two characters per byte.
+        let chars: Vec<char> = id.chars().collect();
+        let mut hash = [0u8; 32];
+        for i in 0..16 {
+            let hi = char_to_nibble(chars[i * 2]).unwrap();
+            let lo = char_to_nibble(chars[i * 2 + 1]).unwrap();
+            hash[i] = (hi << 4) | lo;
+        }
+        hash
+    }
+
+    #[test]
+    fn prefix_matches_only_at_start() {
+        let criteria = MatchCriteria::prefix("ok").unwrap();
+        assert!(criteria.matches(&hash_for("okaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")));
+        assert!(!criteria.matches(&hash_for("kaaaaaaaaaaaaaaaaaaaaaaaaaaaaaao")));
+    }
+
+    #[test]
+    fn suffix_matches_only_at_end() {
+        let criteria = MatchCriteria::suffix("ok").unwrap();
+        assert!(criteria.matches(&hash_for("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaok")));
+        assert!(!criteria.matches(&hash_for("okaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")));
+    }
+
+    #[test]
+    fn contains_matches_anywhere() {
+        let criteria = MatchCriteria::contains("ok").unwrap();
+        assert!(criteria.matches(&hash_for("aaaaaaaaaaaaaokaaaaaaaaaaaaaaaaa")));
+        assert!(!criteria.matches(&hash_for("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")));
+    }
+
+    #[test]
+    fn pattern_wildcards_and_classes() {
+        let criteria = MatchCriteria::pattern("a[a-c].").unwrap();
+        assert!(criteria.matches(&hash_for("abpaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")));
+        assert!(!criteria.matches(&hash_for("adpaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")));
+    }
+
+    #[test]
+    fn leading_counts_zero_nibbles() {
+        let criteria = MatchCriteria::leading(3);
+        assert_eq!(criteria.expected_attempts(), 16u128.pow(3));
+        assert!(criteria.matches(&hash_for("aaabaaaaaaaaaaaaaaaaaaaaaaaaaaaa")));
+        assert!(!criteria.matches(&hash_for("aabbaaaaaaaaaaaaaaaaaaaaaaaaaaaa")));
+    }
+
+    #[test]
+    fn unmatchable_never_matches() {
+        let criteria = MatchCriteria::unmatchable();
+        assert!(!criteria.matches(&hash_for("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")));
+        assert!(!criteria.matches(&hash_for("pppppppppppppppppppppppppppppppp")));
+    }
+
+    #[test]
+    fn median_eta_is_ln2_times_mean() {
+        let criteria = MatchCriteria::leading(3);
+        let mean = criteria.eta_secs(1000.0).unwrap();
+        let median = criteria.median_eta_secs(1000.0).unwrap();
+        assert!((median - mean * std::f64::consts::LN_2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cumulative_probability_grows_with_attempts() {
+        let criteria = MatchCriteria::leading(3);
+        let expected = criteria.expected_attempts() as u64;
+        assert_eq!(criteria.cumulative_probability(0), 0.0);
+        assert!(criteria.cumulative_probability(expected) > 0.6);
+        assert!(criteria.cumulative_probability(expected) < criteria.cumulative_probability(expected * 10));
+    }
+}
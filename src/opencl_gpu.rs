@@ -0,0 +1,606 @@
+//! OpenCL support for AMD/Intel (and any other OpenCL-capable) GPUs,
+//! complementing the Metal and CUDA backends. Like `cuda_gpu`, this loads the
+//! ICD loader at runtime via `dlopen`/`LoadLibrary` (through `libloading`)
+//! instead of linking against it at build time, and compiles the kernel from
+//! source (`src/vanity_id.cl`) at runtime with `clBuildProgram` — OpenCL has
+//! no offline bytecode format to precompile the way CUDA has PTX.
+
+use crate::backend::VanityBackend;
+use crate::match_criteria::MatchCriteria;
+use libloading::{Library, Symbol};
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::c_char;
+use std::sync::OnceLock;
+
+type ClInt = i32;
+type ClUint = u32;
+type ClBitfield = u64;
+type ClPlatformId = *mut c_void;
+type ClDeviceId = *mut c_void;
+type ClContext = *mut c_void;
+type ClCommandQueue = *mut c_void;
+type ClProgram = *mut c_void;
+type ClKernel = *mut c_void;
+type ClMem = *mut c_void;
+
+const CL_SUCCESS: ClInt = 0;
+const CL_DEVICE_TYPE_GPU: ClBitfield = 1 << 2;
+const CL_DEVICE_NAME: ClUint = 0x102B;
+const CL_MEM_READ_WRITE: ClBitfield = 1 << 0;
+const CL_MEM_READ_ONLY: ClBitfield = 1 << 2;
+const CL_MEM_COPY_HOST_PTR: ClBitfield = 1 << 5;
+const CL_PROGRAM_BUILD_LOG: ClUint = 0x1183;
+
+/// Kernel source, compiled at runtime — see src/vanity_id.cl for the device
+/// code (SHA-256 and MAPPING mirroring main.rs, criteria_matches mirroring
+/// match_criteria.rs).
+static KERNEL_SOURCE: &str = include_str!("vanity_id.cl");
+
+macro_rules! load_symbols {
+    ($lib:expr, { $($field:ident : $ty:ty = $name:literal),+ $(,)? }) => {
+        Driver {
+            $(
+                $field: unsafe {
+                    let sym: Symbol<$ty> = $lib.get(concat!($name, "\0").as_bytes())
+                        .map_err(|e| format!("missing OpenCL symbol {}: {}", $name, e))?;
+                    *sym
+                },
+            )+
+            _lib: $lib,
+        }
+    };
+}
+
+#[allow(non_snake_case)]
+struct Driver {
+    _lib: Library,
+    clGetPlatformIDs: unsafe extern "C" fn(ClUint, *mut ClPlatformId, *mut ClUint) -> ClInt,
+    clGetDeviceIDs: unsafe extern "C" fn(
+        ClPlatformId,
+        ClBitfield,
+        ClUint,
+        *mut ClDeviceId,
+        *mut ClUint,
+    ) -> ClInt,
+    clGetDeviceInfo:
+        unsafe extern "C" fn(ClDeviceId, ClUint, usize, *mut c_void, *mut usize) -> ClInt,
+    clCreateContext: unsafe extern "C" fn(
+        *const isize,
+        ClUint,
+        *const ClDeviceId,
+        Option<unsafe extern "C" fn(*const c_char, *const c_void, usize, *mut c_void)>,
+        *mut c_void,
+        *mut ClInt,
+    ) -> ClContext,
+    clCreateCommandQueue:
+        unsafe extern "C" fn(ClContext, ClDeviceId, ClBitfield, *mut ClInt) -> ClCommandQueue,
+    clCreateProgramWithSource: unsafe extern "C" fn(
+        ClContext,
+        ClUint,
+        *const *const c_char,
+        *const usize,
+        *mut ClInt,
+    ) -> ClProgram,
+    clBuildProgram: unsafe extern "C" fn(
+        ClProgram,
+        ClUint,
+        *const ClDeviceId,
+        *const c_char,
+        Option<unsafe extern "C" fn(ClProgram, *mut c_void)>,
+        *mut c_void,
+    ) -> ClInt,
+    clGetProgramBuildInfo: unsafe extern "C" fn(
+        ClProgram,
+        ClDeviceId,
+        ClUint,
+        usize,
+        *mut c_void,
+        *mut usize,
+    ) -> ClInt,
+    clCreateKernel: unsafe extern "C" fn(ClProgram, *const c_char, *mut ClInt) -> ClKernel,
+    clCreateBuffer:
+        unsafe extern "C" fn(ClContext, ClBitfield, usize, *mut c_void, *mut ClInt) -> ClMem,
+    clSetKernelArg: unsafe extern "C" fn(ClKernel, ClUint, usize, *const c_void) -> ClInt,
+    clEnqueueWriteBuffer: unsafe extern "C" fn(
+        ClCommandQueue,
+        ClMem,
+        ClUint,
+        usize,
+        usize,
+        *const c_void,
+        ClUint,
+        *const c_void,
+        *mut c_void,
+    ) -> ClInt,
+    clEnqueueNDRangeKernel: unsafe extern "C" fn(
+        ClCommandQueue,
+        ClKernel,
+        ClUint,
+        *const usize,
+        *const usize,
+        *const usize,
+        ClUint,
+        *const c_void,
+        *mut c_void,
+    ) -> ClInt,
+    clEnqueueReadBuffer: unsafe extern "C" fn(
+        ClCommandQueue,
+        ClMem,
+        ClUint,
+        usize,
+        usize,
+        *mut c_void,
+        ClUint,
+        *const c_void,
+        *mut c_void,
+    ) -> ClInt,
+    clFinish: unsafe extern "C" fn(ClCommandQueue) -> ClInt,
+    clReleaseMemObject: unsafe extern "C" fn(ClMem) -> ClInt,
+    clReleaseKernel: unsafe extern "C" fn(ClKernel) -> ClInt,
+    clReleaseProgram: unsafe extern "C" fn(ClProgram) -> ClInt,
+    clReleaseCommandQueue: unsafe extern "C" fn(ClCommandQueue) -> ClInt,
+    clReleaseContext: unsafe extern "C" fn(ClContext) -> ClInt,
+}
+
+fn driver_library_names() -> &'static [&'static str] {
+    if cfg!(target_os = "windows") {
+        &["OpenCL.dll"]
+    } else if cfg!(target_os = "macos") {
+        &["/System/Library/Frameworks/OpenCL.framework/OpenCL"]
+    } else {
+        &["libOpenCL.so", "libOpenCL.so.1"]
+    }
+}
+
+fn load_driver() -> Result<Driver, String> {
+    let mut last_err = "no OpenCL ICD loader names configured".to_string();
+    for name in driver_library_names() {
+        match unsafe { Library::new(name) } {
+            Ok(lib) => {
+                let driver: Result<Driver, String> = (|| {
+                    Ok(load_symbols!(lib, {
+                        clGetPlatformIDs: unsafe extern "C" fn(ClUint, *mut ClPlatformId, *mut ClUint) -> ClInt = "clGetPlatformIDs",
+                        clGetDeviceIDs: unsafe extern "C" fn(ClPlatformId, ClBitfield, ClUint, *mut ClDeviceId, *mut ClUint) -> ClInt = "clGetDeviceIDs",
+                        clGetDeviceInfo: unsafe extern "C" fn(ClDeviceId, ClUint, usize, *mut c_void, *mut usize) -> ClInt = "clGetDeviceInfo",
+                        clCreateContext: unsafe extern "C" fn(*const isize, ClUint, *const ClDeviceId, Option<unsafe extern "C" fn(*const c_char, *const c_void, usize, *mut c_void)>, *mut c_void, *mut ClInt) -> ClContext = "clCreateContext",
+                        clCreateCommandQueue: unsafe extern "C" fn(ClContext, ClDeviceId, ClBitfield, *mut ClInt) -> ClCommandQueue = "clCreateCommandQueue",
+                        clCreateProgramWithSource: unsafe extern "C" fn(ClContext, ClUint, *const *const c_char, *const usize, *mut ClInt) -> ClProgram = "clCreateProgramWithSource",
+                        clBuildProgram: unsafe extern "C" fn(ClProgram, ClUint, *const ClDeviceId, *const c_char, Option<unsafe extern "C" fn(ClProgram, *mut c_void)>, *mut c_void) -> ClInt = "clBuildProgram",
+                        clGetProgramBuildInfo: unsafe extern "C" fn(ClProgram, ClDeviceId, ClUint, usize, *mut c_void, *mut usize) -> ClInt = "clGetProgramBuildInfo",
+                        clCreateKernel: unsafe extern "C" fn(ClProgram, *const c_char, *mut ClInt) -> ClKernel = "clCreateKernel",
+                        clCreateBuffer: unsafe extern "C" fn(ClContext, ClBitfield, usize, *mut c_void, *mut ClInt) -> ClMem = "clCreateBuffer",
+                        clSetKernelArg: unsafe extern "C" fn(ClKernel, ClUint, usize, *const c_void) -> ClInt = "clSetKernelArg",
+                        clEnqueueWriteBuffer: unsafe extern "C" fn(ClCommandQueue, ClMem, ClUint, usize, usize, *const c_void, ClUint, *const c_void, *mut c_void) -> ClInt = "clEnqueueWriteBuffer",
+                        clEnqueueNDRangeKernel: unsafe extern "C" fn(ClCommandQueue, ClKernel, ClUint, *const usize, *const usize, *const usize, ClUint, *const c_void, *mut c_void) -> ClInt = "clEnqueueNDRangeKernel",
+                        clEnqueueReadBuffer: unsafe extern "C" fn(ClCommandQueue, ClMem, ClUint, usize, usize, *mut c_void, ClUint, *const c_void, *mut c_void) -> ClInt = "clEnqueueReadBuffer",
+                        clFinish: unsafe extern "C" fn(ClCommandQueue) -> ClInt = "clFinish",
+                        clReleaseMemObject: unsafe extern "C" fn(ClMem) -> ClInt = "clReleaseMemObject",
+                        clReleaseKernel: unsafe extern "C" fn(ClKernel) -> ClInt = "clReleaseKernel",
+                        clReleaseProgram: unsafe extern "C" fn(ClProgram) -> ClInt = "clReleaseProgram",
+                        clReleaseCommandQueue: unsafe extern "C" fn(ClCommandQueue) -> ClInt = "clReleaseCommandQueue",
+                        clReleaseContext: unsafe extern "C" fn(ClContext) -> ClInt = "clReleaseContext",
+                    }))
+                })();
+
+                match driver {
+                    Ok(driver) => return Ok(driver),
+                    Err(e) => last_err = e,
+                }
+            }
+            Err(e) => last_err = format!("failed to load {}: {}", name, e),
+        }
+    }
+    Err(last_err)
+}
+
+fn driver() -> Result<&'static Driver, String> {
+    static DRIVER: OnceLock<Result<Driver, String>> = OnceLock::new();
+    DRIVER
+        .get_or_init(load_driver)
+        .as_ref()
+        .map_err(|e| e.clone())
+}
+
+/// Enumerates every OpenCL platform the ICD loader reports (a host can have
+/// several — e.g. separate AMD and Intel runtimes installed side by side)
+/// and returns the first GPU device found on any of them, so a machine
+/// where the first-registered platform has no GPU still gets accelerated.
+fn first_gpu_device(driver: &Driver) -> Result<(ClPlatformId, ClDeviceId), Box<dyn std::error::Error>> {
+    let mut num_platforms: ClUint = 0;
+    let result = unsafe { (driver.clGetPlatformIDs)(0, std::ptr::null_mut(), &mut num_platforms) };
+    if result != CL_SUCCESS || num_platforms == 0 {
+        return Err(format!("No OpenCL platform found (error {})", result).into());
+    }
+
+    let mut platforms = vec![std::ptr::null_mut(); num_platforms as usize];
+    let result = unsafe {
+        (driver.clGetPlatformIDs)(num_platforms, platforms.as_mut_ptr(), std::ptr::null_mut())
+    };
+    if result != CL_SUCCESS {
+        return Err(format!("Failed to list OpenCL platforms (error {})", result).into());
+    }
+
+    for platform in platforms {
+        let mut device: ClDeviceId = std::ptr::null_mut();
+        let mut num_devices: ClUint = 0;
+        let result = unsafe {
+            (driver.clGetDeviceIDs)(
+                platform,
+                CL_DEVICE_TYPE_GPU,
+                1,
+                &mut device,
+                &mut num_devices,
+            )
+        };
+        if result == CL_SUCCESS && num_devices > 0 {
+            return Ok((platform, device));
+        }
+    }
+
+    Err("No OpenCL GPU device found on any platform".into())
+}
+
+pub struct OpenClVanityGenerator {
+    device_id: ClDeviceId,
+    device_name: String,
+    context: ClContext,
+    queue: ClCommandQueue,
+    program: ClProgram,
+    kernel: ClKernel,
+}
+
+// Handles are only ever touched through `&self` on the owning thread;
+// `OpenClVanityGenerator` is not `Sync` but is safe to hand off to another
+// thread and use there, same rationale as `CudaVanityGenerator`.
+unsafe impl Send for OpenClVanityGenerator {}
+
+impl OpenClVanityGenerator {
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        let driver = driver().map_err(|e| format!("OpenCL ICD loader unavailable: {}", e))?;
+        let (_platform, device) = first_gpu_device(driver)?;
+
+        let mut name_buffer = [0u8; 256];
+        let mut name_len = 0usize;
+        unsafe {
+            (driver.clGetDeviceInfo)(
+                device,
+                CL_DEVICE_NAME,
+                name_buffer.len(),
+                name_buffer.as_mut_ptr() as *mut c_void,
+                &mut name_len,
+            )
+        };
+        let device_name =
+            CStr::from_bytes_with_nul(&name_buffer[..name_len.min(name_buffer.len())])
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|_| {
+                    String::from_utf8_lossy(&name_buffer[..name_len.saturating_sub(1)]).into_owned()
+                });
+
+        let mut err: ClInt = CL_SUCCESS;
+        let context = unsafe {
+            (driver.clCreateContext)(
+                std::ptr::null(),
+                1,
+                &device,
+                None,
+                std::ptr::null_mut(),
+                &mut err,
+            )
+        };
+        if context.is_null() || err != CL_SUCCESS {
+            return Err(format!("Failed to create OpenCL context (error {})", err).into());
+        }
+
+        let queue = unsafe { (driver.clCreateCommandQueue)(context, device, 0, &mut err) };
+        if queue.is_null() || err != CL_SUCCESS {
+            unsafe { (driver.clReleaseContext)(context) };
+            return Err(format!("Failed to create OpenCL command queue (error {})", err).into());
+        }
+
+        let source_ptr = KERNEL_SOURCE.as_ptr() as *const c_char;
+        let source_len = KERNEL_SOURCE.len();
+        let program = unsafe {
+            (driver.clCreateProgramWithSource)(context, 1, &source_ptr, &source_len, &mut err)
+        };
+        if program.is_null() || err != CL_SUCCESS {
+            unsafe {
+                (driver.clReleaseCommandQueue)(queue);
+                (driver.clReleaseContext)(context);
+            }
+            return Err(format!("Failed to create OpenCL program (error {})", err).into());
+        }
+
+        let build_options = CString::new("").unwrap();
+        let result = unsafe {
+            (driver.clBuildProgram)(
+                program,
+                1,
+                &device,
+                build_options.as_ptr(),
+                None,
+                std::ptr::null_mut(),
+            )
+        };
+        if result != CL_SUCCESS {
+            let mut log = [0u8; 4096];
+            let mut log_len = 0usize;
+            unsafe {
+                (driver.clGetProgramBuildInfo)(
+                    program,
+                    device,
+                    CL_PROGRAM_BUILD_LOG,
+                    log.len(),
+                    log.as_mut_ptr() as *mut c_void,
+                    &mut log_len,
+                )
+            };
+            let log = String::from_utf8_lossy(&log[..log_len.min(log.len())]).into_owned();
+            unsafe {
+                (driver.clReleaseProgram)(program);
+                (driver.clReleaseCommandQueue)(queue);
+                (driver.clReleaseContext)(context);
+            }
+            return Err(format!("Failed to build OpenCL program: {}", log).into());
+        }
+
+        let kernel_name = CString::new("vanity_search_kernel").unwrap();
+        let kernel = unsafe { (driver.clCreateKernel)(program, kernel_name.as_ptr(), &mut err) };
+        if kernel.is_null() || err != CL_SUCCESS {
+            unsafe {
+                (driver.clReleaseProgram)(program);
+                (driver.clReleaseCommandQueue)(queue);
+                (driver.clReleaseContext)(context);
+            }
+            return Err(format!("Failed to find vanity_search_kernel (error {})", err).into());
+        }
+
+        println!("Using OpenCL GPU: {}", device_name);
+
+        Ok(OpenClVanityGenerator {
+            device_id: device,
+            device_name,
+            context,
+            queue,
+            program,
+            kernel,
+        })
+    }
+
+    pub fn get_device_name(&self) -> String {
+        self.device_name.clone()
+    }
+
+    pub fn search_vanity_id(
+        &self,
+        criteria: &MatchCriteria,
+        start_counter: u64,
+        batch_size: u64,
+    ) -> Result<Option<(u64, [u8; 32])>, Box<dyn std::error::Error>> {
+        let driver = driver().map_err(|e| format!("OpenCL ICD loader unavailable: {}", e))?;
+
+        let spec = criteria.gpu_spec();
+        let mut err: ClInt = CL_SUCCESS;
+
+        let spec_buf = unsafe {
+            (driver.clCreateBuffer)(
+                self.context,
+                CL_MEM_READ_ONLY | CL_MEM_COPY_HOST_PTR,
+                spec.len() * std::mem::size_of::<u32>(),
+                spec.as_ptr() as *mut c_void,
+                &mut err,
+            )
+        };
+        if spec_buf.is_null() || err != CL_SUCCESS {
+            return Err(format!(
+                "Failed to allocate OpenCL match-criteria buffer (error {})",
+                err
+            )
+            .into());
+        }
+
+        // Sentinel: u32::MAX means "no match"; the kernel claims the lowest
+        // matching global id via atomic_min, so whichever work-item wins is
+        // deterministic regardless of completion order.
+        let sentinel = u32::MAX;
+        let result_buf = unsafe {
+            (driver.clCreateBuffer)(
+                self.context,
+                CL_MEM_READ_WRITE,
+                std::mem::size_of::<u32>(),
+                std::ptr::null_mut(),
+                &mut err,
+            )
+        };
+        if result_buf.is_null() || err != CL_SUCCESS {
+            unsafe { (driver.clReleaseMemObject)(spec_buf) };
+            return Err(format!("Failed to allocate OpenCL result buffer (error {})", err).into());
+        }
+        let result = unsafe {
+            (driver.clEnqueueWriteBuffer)(
+                self.queue,
+                result_buf,
+                1,
+                0,
+                std::mem::size_of::<u32>(),
+                &sentinel as *const u32 as *const c_void,
+                0,
+                std::ptr::null(),
+                std::ptr::null_mut(),
+            )
+        };
+        if result != CL_SUCCESS {
+            unsafe {
+                (driver.clReleaseMemObject)(spec_buf);
+                (driver.clReleaseMemObject)(result_buf);
+            }
+            return Err(cl_error(
+                result,
+                "Failed to initialize OpenCL result buffer",
+            ));
+        }
+
+        let args: [(usize, *const c_void); 4] = [
+            (
+                std::mem::size_of::<ClMem>(),
+                &spec_buf as *const ClMem as *const c_void,
+            ),
+            (
+                std::mem::size_of::<u64>(),
+                &start_counter as *const u64 as *const c_void,
+            ),
+            (
+                std::mem::size_of::<u64>(),
+                &batch_size as *const u64 as *const c_void,
+            ),
+            (
+                std::mem::size_of::<ClMem>(),
+                &result_buf as *const ClMem as *const c_void,
+            ),
+        ];
+        for (i, (size, ptr)) in args.iter().enumerate() {
+            let result = unsafe { (driver.clSetKernelArg)(self.kernel, i as ClUint, *size, *ptr) };
+            if result != CL_SUCCESS {
+                unsafe {
+                    (driver.clReleaseMemObject)(spec_buf);
+                    (driver.clReleaseMemObject)(result_buf);
+                }
+                return Err(cl_error(result, "Failed to set OpenCL kernel argument"));
+            }
+        }
+
+        let global_work_size = batch_size as usize;
+        let result = unsafe {
+            (driver.clEnqueueNDRangeKernel)(
+                self.queue,
+                self.kernel,
+                1,
+                std::ptr::null(),
+                &global_work_size,
+                std::ptr::null(),
+                0,
+                std::ptr::null(),
+                std::ptr::null_mut(),
+            )
+        };
+        if result != CL_SUCCESS {
+            unsafe {
+                (driver.clReleaseMemObject)(spec_buf);
+                (driver.clReleaseMemObject)(result_buf);
+            }
+            return Err(cl_error(result, "OpenCL kernel dispatch failed"));
+        }
+
+        let mut winning_id: u32 = sentinel;
+        let result = unsafe {
+            (driver.clEnqueueReadBuffer)(
+                self.queue,
+                result_buf,
+                1,
+                0,
+                std::mem::size_of::<u32>(),
+                &mut winning_id as *mut u32 as *mut c_void,
+                0,
+                std::ptr::null(),
+                std::ptr::null_mut(),
+            )
+        };
+        unsafe {
+            (driver.clFinish)(self.queue);
+            (driver.clReleaseMemObject)(spec_buf);
+            (driver.clReleaseMemObject)(result_buf);
+        }
+        if result != CL_SUCCESS {
+            return Err(cl_error(result, "Failed to read back OpenCL result buffer"));
+        }
+
+        if winning_id == sentinel || (winning_id as u64) >= batch_size {
+            return Ok(None);
+        }
+
+        // The key is a pure function of the counter, so there's no need to
+        // round-trip the 32 key bytes from the device: recompute them here
+        // from the winning counter instead.
+        let counter = start_counter + winning_id as u64;
+        let key_data = crate::generate_key_data(counter);
+
+        Ok(Some((counter, key_data)))
+    }
+}
+
+fn cl_error(code: ClInt, context: &str) -> Box<dyn std::error::Error> {
+    format!("{} (OpenCL error {})", context, code).into()
+}
+
+impl VanityBackend for OpenClVanityGenerator {
+    fn search(
+        &self,
+        criteria: &MatchCriteria,
+        start_counter: u64,
+        batch_size: u64,
+    ) -> Result<Option<(u64, [u8; 32])>, Box<dyn std::error::Error>> {
+        self.search_vanity_id(criteria, start_counter, batch_size)
+    }
+
+    fn device_name(&self) -> String {
+        self.get_device_name()
+    }
+
+    fn preferred_batch_size(&self) -> u64 {
+        1_000_000
+    }
+}
+
+impl Drop for OpenClVanityGenerator {
+    fn drop(&mut self) {
+        if let Ok(driver) = driver() {
+            unsafe {
+                (driver.clReleaseKernel)(self.kernel);
+                (driver.clReleaseProgram)(self.program);
+                (driver.clReleaseCommandQueue)(self.queue);
+                (driver.clReleaseContext)(self.context);
+            }
+            let _ = self.device_id;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opencl_initialization() {
+        match OpenClVanityGenerator::new() {
+            Ok(gpu) => {
+                println!(
+                    "OpenCL GPU initialized successfully: {}",
+                    gpu.get_device_name()
+                );
+            }
+            Err(e) => {
+                println!("OpenCL GPU initialization failed: {}", e);
+                // Expected on systems without an OpenCL-capable GPU/ICD.
+            }
+        }
+    }
+
+    #[test]
+    fn test_opencl_search_small_batch() {
+        if let Ok(gpu) = OpenClVanityGenerator::new() {
+            let criteria = MatchCriteria::prefix("a").unwrap();
+            match gpu.search_vanity_id(&criteria, 0, 1000) {
+                Ok(result) => {
+                    if let Some((counter, key_data)) = result {
+                        println!("Found match at counter {}: {:?}", counter, key_data);
+                    } else {
+                        println!("No match found in small batch");
+                    }
+                }
+                Err(e) => {
+                    println!("OpenCL search failed: {}", e);
+                }
+            }
+        }
+    }
+}